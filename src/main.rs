@@ -2,6 +2,8 @@ extern crate glob;
 extern crate libc;
 extern crate libloading;
 
+use std::path::{Path, PathBuf};
+
 pub mod support {
 
 use std::{io, env};
@@ -49,10 +51,10 @@ pub struct Clang {
 impl Clang {
     //- Constructors -----------------------------
 
-    fn new(path: PathBuf, args: &[String]) -> Clang {
+    fn new(path: PathBuf, args: &[String], target: Option<&str>, sysroot: Option<&Path>) -> Clang {
         let version = parse_version(&path);
-        let c_search_paths = parse_search_paths(&path, "c", args);
-        let cpp_search_paths = parse_search_paths(&path, "c++", args);
+        let c_search_paths = parse_search_paths(&path, "c", args, target, sysroot);
+        let cpp_search_paths = parse_search_paths(&path, "c++", args, target, sysroot);
         Clang {
             path: path,
             version: version,
@@ -69,8 +71,24 @@ impl Clang {
     /// searched. On OS X systems, `xcodebuild -find clang` will next be queried. Last, the
     /// directories in the system's `PATH` are searched.
     pub fn find(path: Option<&Path>, args: &[String]) -> Option<Clang> {
+        Clang::find_with_target(path, args, None, None)
+    }
+
+    /// Returns a `clang` executable if one can be found, reporting the C/C++ header search paths
+    /// for the supplied cross-compilation target and sysroot rather than for the host.
+    ///
+    /// This behaves exactly like `find`, except that `--target=<target>` and/or
+    /// `--sysroot=<sysroot>` are passed to the `clang -E -x <lang> - -v` invocation used to
+    /// determine `c_search_paths`/`cpp_search_paths`, so the reported directories are the ones
+    /// `clang` would actually search when compiling for that target instead of the host's.
+    pub fn find_with_target(
+        path: Option<&Path>,
+        args: &[String],
+        target: Option<&str>,
+        sysroot: Option<&Path>,
+    ) -> Option<Clang> {
         if let Ok(path) = env::var("CLANG_PATH") {
-            return Some(Clang::new(path.into(), args));
+            return Some(Clang::new(path.into(), args, target, sysroot));
         }
 
         let mut paths = vec![];
@@ -92,11 +110,75 @@ impl Clang {
         let patterns = &[&default[..], &versioned[..]];
         for path in paths {
             if let Some(path) = find(&path, patterns) {
-                return Some(Clang::new(path, args));
+                return Some(Clang::new(path, args, target, sysroot));
             }
         }
         None
     }
+
+    /// Returns the `clang` executable with the highest version satisfying `predicate`, if one can
+    /// be found.
+    ///
+    /// This searches the same locations as `find`, except it does not stop at the first `clang`
+    /// executable found in a searched directory — every candidate is probed with `--version` and
+    /// the one with the highest version for which `predicate` returns `true` is returned. This
+    /// avoids silently binding against an unsuitable `clang` on systems with several installed.
+    pub fn find_matching<F: Fn(CXVersion) -> bool>(
+        path: Option<&Path>,
+        args: &[String],
+        predicate: F,
+    ) -> Option<Clang> {
+        if let Ok(path) = env::var("CLANG_PATH") {
+            let clang = Clang::new(path.into(), args, None, None);
+            return match clang.version {
+                Some(version) if predicate(version) => Some(clang),
+                _ => None,
+            };
+        }
+
+        let mut paths = vec![];
+        if let Some(path) = path {
+            paths.push(path.into());
+        }
+        if let Ok(path) = run_llvm_config(&["--bindir"]) {
+            paths.push(path.into());
+        }
+        if cfg!(target_os="macos") {
+            if let Ok((path, _)) = run("xcodebuild", &["-find", "clang"]) {
+                paths.push(path.into());
+            }
+        }
+        paths.extend(env::split_paths(&env::var("PATH").unwrap()));
+
+        let default = format!("clang{}", env::consts::EXE_SUFFIX);
+        let versioned = format!("clang-[0-9]*{}", env::consts::EXE_SUFFIX);
+        let patterns = &[&default[..], &versioned[..]];
+
+        let mut best: Option<Clang> = None;
+        for directory in paths {
+            for executable in find_all(&directory, patterns) {
+                let clang = Clang::new(executable, args, None, None);
+                let version = match clang.version {
+                    Some(version) => version,
+                    None => continue,
+                };
+                if !predicate(version) {
+                    continue;
+                }
+                let is_better = match best {
+                    Some(ref best) => match best.version {
+                        Some(best_version) => version_key(version) > version_key(best_version),
+                        None => true,
+                    },
+                    None => true,
+                };
+                if is_better {
+                    best = Some(clang);
+                }
+            }
+        }
+        best
+    }
 }
 
 //================================================
@@ -117,6 +199,28 @@ fn find(directory: &Path, patterns: &[&str]) -> Option<PathBuf> {
     None
 }
 
+/// Returns every match to the supplied glob patterns in the supplied directory, unlike `find`
+/// which stops at the first one.
+fn find_all(directory: &Path, patterns: &[&str]) -> Vec<PathBuf> {
+    let mut found = vec![];
+    for pattern in patterns {
+        let pattern = directory.join(pattern).to_string_lossy().into_owned();
+        if let Ok(paths) = glob::glob(&pattern) {
+            for path in paths.filter_map(|p| p.ok()) {
+                if path.is_file() && is_executable(&path).unwrap_or(false) {
+                    found.push(path);
+                }
+            }
+        }
+    }
+    found
+}
+
+/// Returns a tuple of a `CXVersion`'s components suitable for ordering comparisons.
+fn version_key(version: CXVersion) -> (c_int, c_int, c_int) {
+    (version.Major, version.Minor, version.Subminor)
+}
+
 #[cfg(unix)]
 fn is_executable(path: &Path) -> io::Result<bool> {
     use libc;
@@ -158,10 +262,21 @@ fn parse_version_number(number: &str) -> Option<c_int> {
 }
 
 /// Parses the version from the output of a `clang` executable if possible.
+///
+/// Vendor-prefixed banners (e.g., Apple's `"Apple clang version 14.0.0 (clang-1400.0.29.202)"`)
+/// are handled by anchoring on `"clang version "`/`"LLVM version "` before falling back to a bare
+/// `"version "`, and by discarding anything from the first `(` onward so trailing build metadata
+/// doesn't get pulled into the version token.
 fn parse_version(path: &Path) -> Option<CXVersion> {
     let output = run_clang(path, &["--version"]).0;
-    let start = try_opt!(output.find("version ")) + 8;
-    let mut numbers = try_opt!(output[start..].split_whitespace().nth(0)).split('.');
+    let start = try_opt!(
+        output.find("clang version ").map(|i| i + 14)
+            .or_else(|| output.find("LLVM version ").map(|i| i + 13))
+            .or_else(|| output.find("version ").map(|i| i + 8))
+    );
+    let token = try_opt!(output[start..].split_whitespace().nth(0));
+    let token = token.split('(').next().unwrap_or(token);
+    let mut numbers = token.split('.');
     let major = try_opt!(numbers.next().and_then(parse_version_number));
     let minor = try_opt!(numbers.next().and_then(parse_version_number));
     let subminor = numbers.next().and_then(parse_version_number).unwrap_or(0);
@@ -169,15 +284,117 @@ fn parse_version(path: &Path) -> Option<CXVersion> {
 }
 
 /// Parses the search paths from the output of a `clang` executable if possible.
-fn parse_search_paths(path: &Path, language: &str, args: &[String]) -> Option<Vec<PathBuf>> {
-    let mut clang_args = vec!["-E", "-x", language, "-", "-v"];
-    clang_args.extend(args.iter().map(|s| &**s));
+///
+/// If a `target` and/or `sysroot` are supplied, they are passed to `clang` via `--target=`/
+/// `--sysroot=` so the reported search paths are the ones for that cross-compilation target
+/// rather than for the host.
+fn parse_search_paths(
+    path: &Path,
+    language: &str,
+    args: &[String],
+    target: Option<&str>,
+    sysroot: Option<&Path>,
+) -> Option<Vec<PathBuf>> {
+    let mut clang_args = vec!["-E".to_string(), "-x".to_string(), language.to_string(), "-".to_string(), "-v".to_string()];
+    if let Some(target) = target {
+        clang_args.push(format!("--target={}", target));
+    }
+    if let Some(sysroot) = sysroot {
+        clang_args.push(format!("--sysroot={}", sysroot.display()));
+    }
+    clang_args.extend(args.iter().cloned());
+    let clang_args = clang_args.iter().map(|s| &**s).collect::<Vec<_>>();
     let output = run_clang(path, &clang_args).1;
-    let start = try_opt!(output.find("#include <...> search starts here:")) + 34;
+    let start = match output.find("#include <...> search starts here:") {
+        Some(start) => start + 34,
+        // `clang-cl` and other MSVC-compatible drivers don't print this GCC-style search path
+        // block, so there's nothing to scrape here. Report no additional search paths instead of
+        // failing outright.
+        None => return Some(vec![]),
+    };
     let end = try_opt!(output.find("End of search list."));
     let paths = output[start..end].replace("(framework directory)", "");
     Some(paths.lines().filter(|l| !l.is_empty()).map(|l| Path::new(l.trim()).into()).collect())
 }
+
+/// Parses a `(major, minor)` version from a `libclang` library's file name, if the name embeds
+/// one (e.g., `libclang.so.11` is `(11, 0)`, `libclang.so.3.9` is `(3, 9)`, `libclang-14.so` is
+/// `(14, 0)`).
+fn parse_library_version(path: &Path) -> Option<(u32, u32)> {
+    let name = path.file_name()?.to_string_lossy().into_owned();
+    let mut numbers = name.split(|c: char| !c.is_digit(10)).filter(|s| !s.is_empty());
+    let major = numbers.next()?.parse().ok()?;
+    let minor = numbers.next().and_then(|n| n.parse().ok()).unwrap_or(0);
+    Some((major, minor))
+}
+
+/// Searches for a `libclang` shared library, returning the path to such a shared library if the
+/// search was successful.
+///
+/// Honors an explicit `LIBCLANG_PATH` override, then falls back to `llvm-config` (itself
+/// overridable via `LLVM_CONFIG_PATH`) and a series of OS-specific backup directories. This is the
+/// same search `load_manually` performs internally, exposed here so callers can resolve a path to
+/// feed into `libloading` (or anything else) without shelling out to `llvm-config` themselves.
+pub fn find_shared_library() -> Result<PathBuf, String> {
+    super::build::find_shared_library()
+}
+
+/// Searches for a statically-linkable `libclang` archive, returning its path if the search was
+/// successful.
+///
+/// Honors an explicit `LIBCLANG_STATIC_PATH` override, then falls back to the same `llvm-config`/
+/// backup-directory search `find_shared_library` performs.
+pub fn find_static_library() -> Result<PathBuf, String> {
+    super::build::find_static_library()
+}
+
+/// Returns the `(major, minor)` version of the `libclang` shared library that `find_shared_library`
+/// would resolve, if one can be found and its version determined from its file name.
+///
+/// This is the same version a runtime consumer would end up loading via `load`/`load_manually`,
+/// exposed here so it can be checked without first loading the library.
+pub fn get_library_version() -> Option<(u32, u32)> {
+    let path = super::build::find_shared_library().ok()?;
+    parse_library_version(&path)
+}
+
+/// A discovered `libclang` shared or static library.
+#[derive(Clone, Debug)]
+pub struct Library {
+    /// The path to the `libclang` library.
+    pub path: PathBuf,
+    /// The path to the `clang` executable that `Clang::find` returns when given the library's
+    /// directory, if one was found. That search honors `CLANG_PATH` and falls back to
+    /// `llvm-config --bindir` and `PATH`, so the executable is not necessarily from the same
+    /// installation as the library.
+    pub clang_executable: Option<PathBuf>,
+    /// The `(major, minor)` version of this library if it could be determined, either from the
+    /// library's file name or, failing that, from the version of `clang_executable`.
+    pub version: Option<(u32, u32)>,
+}
+
+impl Library {
+    /// Returns a `libclang` library if one can be found.
+    ///
+    /// If `directory` is supplied, only that directory is searched. Otherwise, the same search
+    /// `load_manually` uses is performed: an explicit `LIBCLANG_PATH` override, `llvm-config`,
+    /// and then a series of OS-specific backup directories (see `build::find_shared_library`).
+    /// This gives callers a single reliable way to discover the library that would actually be
+    /// loaded, along with its version and a `clang` executable (see `clang_executable`).
+    pub fn find(directory: Option<&Path>, args: &[String]) -> Option<Library> {
+        let path = match directory {
+            Some(directory) => super::build::find_shared_library_in(directory),
+            None => super::build::find_shared_library().ok(),
+        }?;
+
+        let clang = Clang::find(path.parent(), args);
+        let version = parse_library_version(&path).or_else(|| {
+            clang.as_ref().and_then(|c| c.version).map(|v| (v.Major as u32, v.Minor as u32))
+        });
+
+        Some(Library { path: path, clang_executable: clang.map(|c| c.path), version: version })
+    }
+}
 }
 
 macro_rules! link {
@@ -197,59 +414,121 @@ macro_rules! link {
     );
 
     ($($(#[cfg($cfg:meta)])* pub fn $name:ident($($pname:ident: $pty:ty), *) $(-> $ret:ty)*;)+) => (
-        use std::cell::{RefCell};
-        use std::sync::{Arc};
+        #[cfg(feature="runtime")]
+        use std::cell::{Cell, RefCell};
+        #[cfg(feature="runtime")]
+        use std::sync::{Arc, Mutex, Once, ONCE_INIT};
 
         /// The set of functions loaded dynamically.
+        ///
+        /// Every `clang_*` symbol this build of the crate was compiled to know about gets a slot
+        /// here — whether a slot is actually populated depends on whether `libloading` finds the
+        /// symbol in the library loaded at runtime. This lets a single build of this crate run
+        /// against an older or newer `libclang` than its compile-time features name, without
+        /// requiring the deployed library to match exactly; but a slot's type still names the
+        /// opaque/cenum types its own `gte_clang_*` feature brought into scope, so the slot itself
+        /// must stay behind that same feature.
+        #[cfg(feature="runtime")]
         #[derive(Debug, Default)]
         pub struct Functions {
             $($(#[cfg($cfg)])* pub $name: Option<unsafe extern fn($($pname: $pty), *) $(-> $ret)*>,)+
         }
 
         /// A dynamically loaded instance of the `libclang` library.
+        #[cfg(feature="runtime")]
         #[derive(Debug)]
         pub struct SharedLibrary {
             library: libloading::Library,
+            path: PathBuf,
             pub functions: Functions,
         }
 
+        #[cfg(feature="runtime")]
         impl SharedLibrary {
             //- Constructors -----------------------------
 
-            fn new(library: libloading::Library) -> SharedLibrary {
-                SharedLibrary { library: library, functions: Functions::default() }
+            fn new(library: libloading::Library, path: PathBuf) -> SharedLibrary {
+                SharedLibrary { library: library, path: path, functions: Functions::default() }
             }
         }
 
+        #[cfg(feature="runtime")]
         thread_local!(static LIBRARY: RefCell<Option<Arc<SharedLibrary>>> = RefCell::new(None));
 
         /// Returns whether a `libclang` shared library is loaded on this thread.
+        #[cfg(feature="runtime")]
         pub fn is_loaded() -> bool {
             LIBRARY.with(|l| l.borrow().is_some())
         }
 
+        // A process-global instance of the loaded `libclang`, shared across threads so that
+        // `load`/`load_global` only need to open the shared library once per process instead of
+        // once per thread.
+        #[cfg(feature="runtime")]
+        static GLOBAL_LIBRARY_INIT: Once = ONCE_INIT;
+        #[cfg(feature="runtime")]
+        static mut GLOBAL_LIBRARY: Option<Mutex<Option<Arc<SharedLibrary>>>> = None;
+
+        #[cfg(feature="runtime")]
+        fn global_library() -> &'static Mutex<Option<Arc<SharedLibrary>>> {
+            unsafe {
+                GLOBAL_LIBRARY_INIT.call_once(|| GLOBAL_LIBRARY = Some(Mutex::new(None)));
+                GLOBAL_LIBRARY.as_ref().unwrap()
+            }
+        }
+
+        #[cfg(feature="runtime")]
+        thread_local!(static USE_GLOBAL_FALLBACK: Cell<bool> = Cell::new(true));
+
+        /// Sets whether this thread automatically reuses the global `libclang` instance (set by
+        /// `load`/`load_global` on any thread) when this thread's own TLS slot is empty.
+        ///
+        /// This is enabled by default; pass `false` to opt a thread out and require it to call
+        /// `load`/`set_library` explicitly instead.
+        #[cfg(feature="runtime")]
+        #[allow(dead_code)]
+        pub fn set_use_global_fallback(enabled: bool) {
+            USE_GLOBAL_FALLBACK.with(|u| u.set(enabled));
+        }
+
+        #[cfg(feature="runtime")]
         fn with_library<T, F>(f: F) -> Option<T> where F: FnOnce(&SharedLibrary) -> T {
-            LIBRARY.with(|l| {
-                match l.borrow().as_ref() {
-                    Some(library) => Some(f(&library)),
-                    _ => None,
-                }
-            })
+            let local = LIBRARY.with(|l| l.borrow().clone());
+            let library = match local {
+                Some(library) => Some(library),
+                None if USE_GLOBAL_FALLBACK.with(|u| u.get()) => {
+                    let global = global_library().lock().unwrap().clone();
+                    if let Some(ref library) = global {
+                        LIBRARY.with(|l| *l.borrow_mut() = Some(library.clone()));
+                    }
+                    global
+                },
+                None => None,
+            };
+            library.map(|library| f(&library))
         }
 
         $(
             $(#[cfg($cfg)])*
+            #[cfg(feature="runtime")]
             pub unsafe fn $name($($pname: $pty), *) $(-> $ret)* {
                 let f = with_library(|l| {
                     match l.functions.$name {
                         Some(f) => f,
-                        _ => panic!(concat!("function not loaded: ", stringify!($name))),
+                        _ => panic!(concat!(
+                            "libclang function `", stringify!($name),
+                            "` is not available (the loaded libclang may be too old)",
+                        )),
                     }
-                }).expect("a `libclang` shared library is not loaded on this thread");
+                }).expect(concat!(
+                    "libclang function `", stringify!($name), "` is not loaded ",
+                    "(no `libclang` shared library is loaded on this thread)",
+                ));
                 f($($pname), *)
             }
 
             $(#[cfg($cfg)])*
+            #[cfg(feature="runtime")]
             pub mod $name {
                 pub fn is_loaded() -> bool {
                     super::with_library(|l| l.functions.$name.is_some()).unwrap_or(false)
@@ -257,25 +536,84 @@ macro_rules! link {
             }
         )+
 
+        /// Returns whether the named `clang_*` symbol was found in the currently loaded
+        /// `libclang`, looked up by its exact symbol name against the dynamic library directly
+        /// rather than through a typed `Functions` field.
+        ///
+        /// Doing the lookup this way means this function works regardless of which `gte_clang_*`
+        /// features this crate happened to be built with — unlike `$name::is_loaded()`, it isn't
+        /// limited to symbols whose slot survived this build's feature gating, so it's the right
+        /// tool when deciding whether to call a newer API based on the library actually loaded at
+        /// runtime (e.g., alongside `SharedLibrary::version`).
+        #[cfg(feature="runtime")]
+        pub fn is_function_loaded(name: &str) -> bool {
+            with_library(|l| unsafe {
+                l.library.get::<unsafe extern fn()>(name.as_bytes())
+            }.is_ok()).unwrap_or(false)
+        }
+
+        #[cfg(feature="runtime")]
         mod load {
             $(link!(@LOAD: $(#[cfg($cfg)])* fn $name($($pname: $pty), *) $(-> $ret)*);)+
         }
 
+        #[cfg(feature="runtime")]
+        thread_local!(static LIBRARY_PATH: RefCell<Option<PathBuf>> = RefCell::new(None));
+
+        /// Forces subsequent calls to `load`/`load_manually(None)` on this thread to load the
+        /// supplied `libclang` shared library file instead of searching for one. Pass `None` to go
+        /// back to searching.
+        ///
+        /// This is useful alongside `list_available` for tools that must match a specific Clang
+        /// ABI: enumerate the installed libraries, pick one by version, then force that exact file
+        /// to be loaded rather than relying on `find_shared_library`'s implicit best match.
+        #[cfg(feature="runtime")]
+        #[allow(dead_code)]
+        pub fn set_library_path(path: Option<PathBuf>) {
+            LIBRARY_PATH.with(|p| *p.borrow_mut() = path);
+        }
+
+        /// Enumerates every valid `libclang` shared library that can be found by the same search
+        /// `find_shared_library` performs, without loading any of them.
+        ///
+        /// Each candidate is paired with its filename-derived `(major, minor)` version, if one
+        /// could be parsed, ordered best match first (the same order `find_shared_library` would
+        /// pick from). Each searched directory contributes at most one candidate, its
+        /// highest-versioned match; other matching files in the same directory are not listed.
+        /// This lets a caller that must match a specific Clang ABI enumerate its
+        /// choices and pick one by version before loading it with `load_manually`.
+        #[cfg(feature="runtime")]
+        #[allow(dead_code)]
+        pub fn list_available() -> Vec<(PathBuf, Option<(u32, u32)>)> {
+            build::list_shared_libraries()
+        }
+
         /// Loads a `libclang` shared library and returns the library instance.
         ///
         /// This function does not attempt to load any functions from the shared library. The caller
         /// is responsible for loading the functions they require.
         ///
+        /// If `path` is supplied, that exact file is loaded instead of searching for one. Otherwise,
+        /// a file forced by `set_library_path` is used if one was set, falling back to the normal
+        /// `find_shared_library` search.
+        ///
         /// # Failures
         ///
         /// * a `libclang` shared library could not be found
         /// * the `libclang` shared library could not be opened
-        pub fn load_manually() -> Result<SharedLibrary, String> {
-            let file = try!(build::find_shared_library());
+        #[cfg(feature="runtime")]
+        pub fn load_manually(path: Option<&Path>) -> Result<SharedLibrary, String> {
+            let file = match path.map(ToOwned::to_owned) {
+                Some(file) => file,
+                None => match LIBRARY_PATH.with(|p| p.borrow().clone()) {
+                    Some(file) => file,
+                    None => try!(build::find_shared_library()),
+                },
+            };
             let library = libloading::Library::new(&file).map_err(|_| {
                 format!("the `libclang` shared library could not be opened: {}", file.display())
             });
-            let mut library = SharedLibrary::new(try!(library));
+            let mut library = SharedLibrary::new(try!(library), file);
             $(load::$name(&mut library);)+
             Ok(library)
         }
@@ -287,22 +625,57 @@ macro_rules! link {
         /// module with the same name as the function (e.g., `clang_createIndex::is_loaded()` for
         /// the `clang_createIndex` function).
         ///
+        /// If no `libclang` has been shared globally yet (see `load_global`), the library loaded
+        /// here is published as the global instance so other threads can reuse it instead of
+        /// opening their own copy.
+        ///
         /// # Failures
         ///
         /// * a `libclang` shared library could not be found
         /// * the `libclang` shared library could not be opened
+        #[cfg(feature="runtime")]
         #[allow(dead_code)]
         pub fn load() -> Result<(), String> {
-            let library = Arc::new(try!(load_manually()));
-            LIBRARY.with(|l| *l.borrow_mut() = Some(library));
+            let library = Arc::new(try!(load_manually(None)));
+            LIBRARY.with(|l| *l.borrow_mut() = Some(library.clone()));
+            let mut global = global_library().lock().unwrap();
+            if global.is_none() {
+                *global = Some(library);
+            }
             Ok(())
         }
 
+        /// Loads a `libclang` shared library for use process-wide, reusing the globally shared
+        /// instance if one is already loaded (by this function or `load`, on any thread) instead
+        /// of opening a new one.
+        ///
+        /// This is the entry point most callers want: a process with many worker threads needs
+        /// only one of them to call `load_global` (or have any thread call `load`) before every
+        /// other thread's first FFI call transparently picks up the same `SharedLibrary` via
+        /// `with_library`'s global fallback. Use `set_library`/`set_use_global_fallback(false)` to
+        /// opt a thread out of the shared instance.
+        ///
+        /// # Failures
+        ///
+        /// * a `libclang` shared library could not be found
+        /// * the `libclang` shared library could not be opened
+        #[cfg(feature="runtime")]
+        #[allow(dead_code)]
+        pub fn load_global() -> Result<(), String> {
+            let global = global_library().lock().unwrap().clone();
+            if let Some(library) = global {
+                LIBRARY.with(|l| *l.borrow_mut() = Some(library));
+                return Ok(());
+            }
+            load()
+        }
+
         /// Unloads the `libclang` shared library in use in the current thread.
         ///
         /// # Failures
         ///
         /// * a `libclang` shared library is not in use in the current thread
+        #[cfg(feature="runtime")]
         pub fn unload() -> Result<(), String> {
             let library = set_library(None);
             if library.is_some() {
@@ -315,6 +688,7 @@ macro_rules! link {
         /// Returns the library instance stored in TLS.
         ///
         /// This functions allows for sharing library instances between threads.
+        #[cfg(feature="runtime")]
         pub fn get_library() -> Option<Arc<SharedLibrary>> {
             LIBRARY.with(|l| l.borrow_mut().clone())
         }
@@ -322,13 +696,94 @@ macro_rules! link {
         /// Sets the library instance stored in TLS and returns the previous library.
         ///
         /// This functions allows for sharing library instances between threads.
+        #[cfg(feature="runtime")]
         pub fn set_library(library: Option<Arc<SharedLibrary>>) -> Option<Arc<SharedLibrary>> {
             LIBRARY.with(|l| mem::replace(&mut *l.borrow_mut(), library))
         }
+
+        /// Returns the version of the `libclang` shared library loaded on this thread (as
+        /// `SharedLibrary::version` reports it), or `None` if no library is loaded.
+        ///
+        /// This lets callers validate the `TryFrom`/`introduced_in` gating logic above against
+        /// the `libclang` that was actually resolved at runtime, rather than the version assumed
+        /// at compile time.
+        #[cfg(feature="runtime")]
+        #[allow(dead_code)]
+        pub fn loaded_version() -> Option<Version> {
+            with_library(|l| l.version()).and_then(|v| v)
+        }
+
+        // Under the `static` feature, `libclang` is linked directly into the binary at compile
+        // time by `mod build`'s static-linking support, so each `$name` simply forwards to the
+        // statically linked symbol instead of going through a dynamically loaded `SharedLibrary`.
+        #[cfg(feature="static")]
+        extern {
+            $($(#[cfg($cfg)])* pub fn $name($($pname: $pty), *) $(-> $ret)*;)+
+        }
+
+        /// Returns whether `libclang` is loaded.
+        ///
+        /// Under the `static` feature, `libclang` is always linked in, so this always returns `true`.
+        #[cfg(feature="static")]
+        pub fn is_loaded() -> bool {
+            true
+        }
+
+        /// A no-op under the `static` feature, since `libclang` is already linked into the binary.
+        #[cfg(feature="static")]
+        #[allow(dead_code)]
+        pub fn load() -> Result<(), String> {
+            Ok(())
+        }
+
+        /// Always returns `None` under the `static` feature, since there is no dynamically loaded
+        /// `SharedLibrary` to probe for a version marker.
+        #[cfg(feature="static")]
+        #[allow(dead_code)]
+        pub fn loaded_version() -> Option<Version> {
+            None
+        }
+
+        // With neither the `static` nor the `runtime` feature enabled, each `$name` is a plain
+        // `extern` declaration resolved at link time against whatever `libclang` the linker is
+        // pointed at; `mod build` emits no link directive for this mode. This is the simplest mode: no `dlopen`, no graceful fallback if `libclang` is absent, but
+        // also no `libloading` dependency.
+        #[cfg(not(any(feature="static", feature="runtime")))]
+        extern {
+            $($(#[cfg($cfg)])* pub fn $name($($pname: $pty), *) $(-> $ret)*;)+
+        }
+
+        /// Returns whether `libclang` is loaded.
+        ///
+        /// With neither the `static` nor the `runtime` feature enabled, `libclang` is linked at
+        /// build time, so this always returns `true`.
+        #[cfg(not(any(feature="static", feature="runtime")))]
+        pub fn is_loaded() -> bool {
+            true
+        }
+
+        /// A no-op with neither the `static` nor the `runtime` feature enabled, since `libclang`
+        /// is already linked into the binary.
+        #[cfg(not(any(feature="static", feature="runtime")))]
+        #[allow(dead_code)]
+        pub fn load() -> Result<(), String> {
+            Ok(())
+        }
+
+        /// Always returns `None` with neither the `static` nor the `runtime` feature enabled,
+        /// since there is no dynamically loaded `SharedLibrary` to probe for a version marker.
+        #[cfg(not(any(feature="static", feature="runtime")))]
+        #[allow(dead_code)]
+        pub fn loaded_version() -> Option<Version> {
+            None
+        }
     )
 }
 
+use std::convert::TryFrom;
+use std::fmt;
 use std::mem;
+use std::ops;
 
 use libc::{c_char, c_int, c_longlong, c_uint, c_ulong, c_ulonglong, c_void, time_t};
 
@@ -337,20 +792,181 @@ pub type CXCursorVisitor = extern fn(CXCursor, CXCursor, CXClientData) -> CXChil
 pub type CXInclusionVisitor = extern fn(CXFile, *mut CXSourceLocation, c_uint, CXClientData);
 
 /// Defines a C enum as a series of constants.
+///
+/// A leading `#[cfg(...)]` on the `enum` itself gates the whole generated type, including its
+/// constants and every trait impl `@reflect` adds for it. Forwarding that one `cfg` everywhere is
+/// done through a single, non-repeated `$meta:meta` fragment (normalized to the always-true
+/// `all()` predicate when the enum carries no `#[cfg(...)]` of its own) rather than the usual
+/// `$(#[$meta:meta])*` pattern, because a zero-or-more-repeated fragment can't be reused inside
+/// the unrelated per-variant `$(...)+` repetition below without the two repetition counts lining
+/// up — binding it once as a single fragment sidesteps that restriction entirely.
 macro_rules! cenum {
-    ($(#[$meta:meta])* enum $name:ident {
+    (#[cfg($meta:meta)] enum $name:ident {
+        $($(#[$vmeta:meta])* const $variant:ident = $value:expr), +,
+    }) => (
+        cenum!(@plain #[cfg($meta)] enum $name {
+            $($(#[$vmeta])* const $variant = $value), +,
+        });
+    );
+    (enum $name:ident {
         $($(#[$vmeta:meta])* const $variant:ident = $value:expr), +,
     }) => (
-        pub type $name = c_int;
+        cenum!(@plain #[cfg(all())] enum $name {
+            $($(#[$vmeta])* const $variant = $value), +,
+        });
+    );
+    (@plain #[cfg($meta:meta)] enum $name:ident {
+        $($(#[$vmeta:meta])* const $variant:ident = $value:expr), +,
+    }) => (
+        #[cfg($meta)]
+        #[repr(transparent)]
+        #[derive(Copy, Clone, PartialEq, Eq, Hash)]
+        pub struct $name(pub c_int);
 
-        $($(#[$vmeta])* pub const $variant: $name = $value;)+
+        $(#[cfg($meta)] $(#[$vmeta])* pub const $variant: $name = $name($value);)+
+
+        cenum!(@reflect #[cfg($meta)] $name, $($variant = $value), +);
+    );
+    (#[cfg($meta:meta)] enum $name:ident {
+        $($(#[$vmeta:meta])* const $variant:ident = $value:expr); +;
+    }) => (
+        cenum!(@flags #[cfg($meta)] enum $name {
+            $($(#[$vmeta])* const $variant = $value); +;
+        });
+    );
+    (enum $name:ident {
+        $($(#[$vmeta:meta])* const $variant:ident = $value:expr); +;
+    }) => (
+        cenum!(@flags #[cfg(all())] enum $name {
+            $($(#[$vmeta])* const $variant = $value); +;
+        });
     );
-    ($(#[$meta:meta])* enum $name:ident {
+    (@flags #[cfg($meta:meta)] enum $name:ident {
         $($(#[$vmeta:meta])* const $variant:ident = $value:expr); +;
     }) => (
-        pub type $name = c_int;
+        #[cfg($meta)]
+        #[repr(transparent)]
+        #[derive(Copy, Clone, Default, PartialEq, Eq, Hash)]
+        pub struct $name(pub c_int);
+
+        $(#[cfg($meta)] $(#[$vmeta])* pub const $variant: $name = $name($value);)+
+
+        cenum!(@reflect #[cfg($meta)] $name, $($variant = $value), +);
+
+        #[cfg($meta)]
+        impl $name {
+            /// Returns the empty set of flags (i.e., all bits unset).
+            pub fn empty() -> $name {
+                $name(0)
+            }
+
+            /// Returns whether `self` contains all of the bits set in `other`.
+            pub fn contains(self, other: $name) -> bool {
+                self.0 & other.0 == other.0
+            }
+
+            /// Returns the set of flags with every bit this type recognizes set.
+            pub fn all() -> $name {
+                $name($($value)|+)
+            }
+        }
+
+        #[cfg($meta)]
+        impl ops::BitOr for $name {
+            type Output = $name;
+
+            fn bitor(self, rhs: $name) -> $name {
+                $name(self.0 | rhs.0)
+            }
+        }
+
+        #[cfg($meta)]
+        impl ops::BitOrAssign for $name {
+            fn bitor_assign(&mut self, rhs: $name) {
+                self.0 |= rhs.0;
+            }
+        }
+
+        #[cfg($meta)]
+        impl ops::BitAnd for $name {
+            type Output = $name;
+
+            fn bitand(self, rhs: $name) -> $name {
+                $name(self.0 & rhs.0)
+            }
+        }
+
+        #[cfg($meta)]
+        impl ops::BitAndAssign for $name {
+            fn bitand_assign(&mut self, rhs: $name) {
+                self.0 &= rhs.0;
+            }
+        }
+
+        #[cfg($meta)]
+        impl ops::Not for $name {
+            type Output = $name;
+
+            fn not(self) -> $name {
+                $name(!self.0)
+            }
+        }
+    );
+    // Shared symbolic-name reflection and `Display`/`Debug` impls for a generated cenum type.
+    // Matches on the raw value rather than the variant path so that aliased variants (two names
+    // sharing one value) and version-gated variants (whose `const` may not exist under the
+    // active `cfg`) both resolve without needing the identifier itself to be in scope.
+    //
+    // Takes the same `#[cfg($meta)]` the enum itself was declared with (normalized to `all()` if
+    // the enum had none) and re-applies it to every impl block generated here, since these impls
+    // reference `$name` as a type/constructor and must be compiled out alongside it whenever the
+    // enum's own gating feature is disabled.
+    (@reflect #[cfg($meta:meta)] $name:ident, $($variant:ident = $value:expr), +) => (
+        #[cfg($meta)]
+        impl $name {
+            /// Returns the symbolic name of this value (e.g., `"CXType_Elaborated"`), if it is a
+            /// value that this version of the crate recognizes.
+            pub fn name(self) -> Option<&'static str> {
+                match self.0 {
+                    $($value => Some(stringify!($variant)),)+
+                    _ => None,
+                }
+            }
+        }
+
+        #[cfg($meta)]
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                match self.name() {
+                    Some(name) => f.write_str(name),
+                    None => write!(f, "{}({})", stringify!($name), self.0),
+                }
+            }
+        }
+
+        #[cfg($meta)]
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                match self.name() {
+                    Some(name) => f.write_str(name),
+                    None => write!(f, "{}", self.0),
+                }
+            }
+        }
+
+        #[cfg($meta)]
+        impl TryFrom<c_int> for $name {
+            type Error = c_int;
 
-        $($(#[$vmeta])* pub const $variant: $name = $value;)+
+            /// Converts a raw value into this cenum type, failing if `value` does not match any
+            /// variant that this build of the crate was compiled with.
+            fn try_from(value: c_int) -> Result<$name, c_int> {
+                match value {
+                    $($value => Ok($name(value)),)+
+                    other => Err(other),
+                }
+            }
+        }
     );
 }
 
@@ -418,6 +1034,21 @@ cenum! {
     }
 }
 
+impl CXCallingConv {
+    /// Returns the `libclang` version that introduced this value, if it is one of the
+    /// variants that was added after this enum's earliest supported version.
+    pub fn introduced_in(self) -> Option<Version> {
+        match self.name() {
+            Some("CXCallingConv_X86RegCall") => Some(Version::V4_0),
+            Some("CXCallingConv_X86VectorCall") => Some(Version::V3_6),
+            Some("CXCallingConv_Swift") => Some(Version::V3_9),
+            Some("CXCallingConv_PreserveMost") => Some(Version::V3_9),
+            Some("CXCallingConv_PreserveAll") => Some(Version::V3_9),
+            _ => None,
+        }
+    }
+}
+
 cenum! {
     enum CXChildVisitResult {
         const CXChildVisit_Break = 0,
@@ -756,6 +1387,66 @@ cenum! {
     }
 }
 
+impl CXCursorKind {
+    /// Returns the spelling that `libclang` itself uses for this cursor kind.
+    ///
+    /// Unlike `name`, this asks the loaded `libclang` directly via `clang_getCursorKindSpelling`,
+    /// so it also reports a sensible spelling for kinds added after this crate was last updated.
+    pub fn clang_spelling(self) -> String {
+        unsafe { cxstring_into_string(clang_getCursorKindSpelling(self)) }
+    }
+
+    /// Returns the `libclang` version that introduced this value, if it is one of the
+    /// variants that was added after this enum's earliest supported version.
+    pub fn introduced_in(self) -> Option<Version> {
+        match self.name() {
+            Some("CXCursor_OMPArraySectionExpr") => Some(Version::V3_8),
+            Some("CXCursor_ObjCAvailabilityCheckExpr") => Some(Version::V3_9),
+            Some("CXCursor_OMPOrderedDirective") => Some(Version::V3_6),
+            Some("CXCursor_OMPAtomicDirective") => Some(Version::V3_6),
+            Some("CXCursor_OMPForSimdDirective") => Some(Version::V3_6),
+            Some("CXCursor_OMPParallelForSimdDirective") => Some(Version::V3_6),
+            Some("CXCursor_OMPTargetDirective") => Some(Version::V3_6),
+            Some("CXCursor_OMPTeamsDirective") => Some(Version::V3_6),
+            Some("CXCursor_OMPTaskgroupDirective") => Some(Version::V3_7),
+            Some("CXCursor_OMPCancellationPointDirective") => Some(Version::V3_7),
+            Some("CXCursor_OMPCancelDirective") => Some(Version::V3_7),
+            Some("CXCursor_OMPTargetDataDirective") => Some(Version::V3_8),
+            Some("CXCursor_OMPTaskLoopDirective") => Some(Version::V3_8),
+            Some("CXCursor_OMPTaskLoopSimdDirective") => Some(Version::V3_8),
+            Some("CXCursor_OMPDistributeDirective") => Some(Version::V3_8),
+            Some("CXCursor_OMPTargetEnterDataDirective") => Some(Version::V3_9),
+            Some("CXCursor_OMPTargetExitDataDirective") => Some(Version::V3_9),
+            Some("CXCursor_OMPTargetParallelDirective") => Some(Version::V3_9),
+            Some("CXCursor_OMPTargetParallelForDirective") => Some(Version::V3_9),
+            Some("CXCursor_OMPTargetUpdateDirective") => Some(Version::V3_9),
+            Some("CXCursor_OMPDistributeParallelForDirective") => Some(Version::V3_9),
+            Some("CXCursor_OMPDistributeParallelForSimdDirective") => Some(Version::V3_9),
+            Some("CXCursor_OMPDistributeSimdDirective") => Some(Version::V3_9),
+            Some("CXCursor_OMPTargetParallelForSimdDirective") => Some(Version::V3_9),
+            Some("CXCursor_OMPTargetSimdDirective") => Some(Version::V4_0),
+            Some("CXCursor_OMPTeamsDistributeDirective") => Some(Version::V4_0),
+            Some("CXCursor_OMPTeamsDistributeSimdDirective") => Some(Version::V4_0),
+            Some("CXCursor_OMPTeamsDistributeParallelForSimdDirective") => Some(Version::V4_0),
+            Some("CXCursor_OMPTeamsDistributeParallelForDirective") => Some(Version::V4_0),
+            Some("CXCursor_OMPTargetTeamsDirective") => Some(Version::V4_0),
+            Some("CXCursor_OMPTargetTeamsDistributeDirective") => Some(Version::V4_0),
+            Some("CXCursor_OMPTargetTeamsDistributeParallelForDirective") => Some(Version::V4_0),
+            Some("CXCursor_OMPTargetTeamsDistributeParallelForSimdDirective") => Some(Version::V4_0),
+            Some("CXCursor_OMPTargetTeamsDistributeSimdDirective") => Some(Version::V4_0),
+            Some("CXCursor_CUDASharedAttr") => Some(Version::V3_6),
+            Some("CXCursor_VisibilityAttr") => Some(Version::V3_8),
+            Some("CXCursor_DLLExport") => Some(Version::V3_8),
+            Some("CXCursor_DLLImport") => Some(Version::V3_8),
+            Some("CXCursor_TypeAliasTemplateDecl") => Some(Version::V3_8),
+            Some("CXCursor_StaticAssert") => Some(Version::V3_9),
+            Some("CXCursor_FriendDecl") => Some(Version::V4_0),
+            Some("CXCursor_OverloadCandidate") => Some(Version::V3_7),
+            _ => None,
+        }
+    }
+}
+
 cenum! {
     #[cfg(feature="gte_clang_5_0")]
     enum CXCursor_ExceptionSpecificationKind {
@@ -864,6 +1555,17 @@ cenum! {
     }
 }
 
+impl CXIdxEntityLanguage {
+    /// Returns the `libclang` version that introduced this value, if it is one of the
+    /// variants that was added after this enum's earliest supported version.
+    pub fn introduced_in(self) -> Option<Version> {
+        match self.name() {
+            Some("CXIdxEntityLang_Swift") => Some(Version::V5_0),
+            _ => None,
+        }
+    }
+}
+
 cenum! {
     enum CXIdxEntityRefKind {
         const CXIdxEntityRef_Direct = 1,
@@ -907,6 +1609,39 @@ cenum! {
     }
 }
 
+cenum! {
+    #[cfg(feature="gte_clang_6_0")]
+    enum CXPrintingPolicyProperty {
+        const CXPrintingPolicy_Indentation = 0,
+        const CXPrintingPolicy_SuppressSpecifiers = 1,
+        const CXPrintingPolicy_SuppressTagKeyword = 2,
+        const CXPrintingPolicy_IncludeTagDefinition = 3,
+        const CXPrintingPolicy_SuppressScope = 4,
+        const CXPrintingPolicy_SuppressUnwrittenScope = 5,
+        const CXPrintingPolicy_SuppressInitializers = 6,
+        const CXPrintingPolicy_ConstantArraySizeAsWritten = 7,
+        const CXPrintingPolicy_AnonymousTagLocations = 8,
+        const CXPrintingPolicy_SuppressStrongLifetime = 9,
+        const CXPrintingPolicy_SuppressLifetimeQualifiers = 10,
+        const CXPrintingPolicy_SuppressTemplateArgsInCXXConstructors = 11,
+        const CXPrintingPolicy_Bool = 12,
+        const CXPrintingPolicy_Restrict = 13,
+        const CXPrintingPolicy_Alignof = 14,
+        const CXPrintingPolicy_UnderscoreAlignof = 15,
+        const CXPrintingPolicy_UseVoidForZeroParams = 16,
+        const CXPrintingPolicy_TerseOutput = 17,
+        const CXPrintingPolicy_PolishForDeclaration = 18,
+        const CXPrintingPolicy_Half = 19,
+        const CXPrintingPolicy_MSWChar = 20,
+        const CXPrintingPolicy_IncludeNewlines = 21,
+        const CXPrintingPolicy_MSVCFormatting = 22,
+        const CXPrintingPolicy_ConstantsAsWritten = 23,
+        const CXPrintingPolicy_SuppressImplicitBase = 24,
+        const CXPrintingPolicy_FullyQualifiedName = 25,
+        const CXPrintingPolicy_LastProperty = 25,
+    }
+}
+
 cenum! {
     enum CXRefQualifierKind {
         const CXRefQualifier_None = 0,
@@ -1120,6 +1855,29 @@ cenum! {
     }
 }
 
+impl CXTypeKind {
+    /// Returns the spelling that `libclang` itself uses for this type kind.
+    ///
+    /// Unlike `name`, this asks the loaded `libclang` directly via `clang_getTypeKindSpelling`,
+    /// so it also reports a sensible spelling for kinds added after this crate was last updated.
+    pub fn clang_spelling(self) -> String {
+        unsafe { cxstring_into_string(clang_getTypeKindSpelling(self)) }
+    }
+
+    /// Returns the `libclang` version that introduced this value, if it is one of the
+    /// variants that was added after this enum's earliest supported version.
+    pub fn introduced_in(self) -> Option<Version> {
+        match self.name() {
+            Some("CXType_Auto") => Some(Version::V3_8),
+            Some("CXType_Float128") | Some("CXType_Elaborated") => Some(Version::V3_9),
+            Some("CXType_Half") | Some("CXType_Pipe") => Some(Version::V5_0),
+            // The OpenCL image/sampler/event/queue types were all added together in 5.0.
+            Some(name) if name.starts_with("CXType_OCL") => Some(Version::V5_0),
+            _ => None,
+        }
+    }
+}
+
 cenum! {
     enum CXTypeLayoutError {
         const CXTypeLayoutError_Invalid = -1,
@@ -1288,6 +2046,17 @@ cenum! {
     }
 }
 
+impl CXObjCPropertyAttrKind {
+    /// Returns the `libclang` version that introduced this value, if it is one of the
+    /// variants that was added after this enum's earliest supported version.
+    pub fn introduced_in(self) -> Option<Version> {
+        match self.name() {
+            Some("CXObjCPropertyAttr_class") => Some(Version::V3_9),
+            _ => None,
+        }
+    }
+}
+
 cenum! {
     enum CXReparse_Flags {
         const CXReparse_None = 0;
@@ -1320,6 +2089,19 @@ cenum! {
     }
 }
 
+impl CXTranslationUnit_Flags {
+    /// Returns the `libclang` version that introduced this value, if it is one of the
+    /// variants that was added after this enum's earliest supported version.
+    pub fn introduced_in(self) -> Option<Version> {
+        match self.name() {
+            Some("CXTranslationUnit_CreatePreambleOnFirstParse") => Some(Version::V3_8),
+            Some("CXTranslationUnit_KeepGoing") => Some(Version::V3_9),
+            Some("CXTranslationUnit_SingleFileParse") => Some(Version::V5_0),
+            _ => None,
+        }
+    }
+}
+
 //================================================
 // Structs
 //================================================
@@ -1345,6 +2127,8 @@ opaque!(CXIdxClientFile);
 opaque!(CXIndex);
 opaque!(CXIndexAction);
 opaque!(CXModule);
+#[cfg(feature="gte_clang_6_0")]
+opaque!(CXPrintingPolicy);
 opaque!(CXRemapping);
 #[cfg(feature="gte_clang_5_0")]
 opaque!(CXTargetInfo);
@@ -1646,6 +2430,29 @@ pub struct CXString {
 
 default!(CXString);
 
+impl CXString {
+    /// Converts this owned `CXString` into a Rust `String`, disposing of the `CXString` in the
+    /// process.
+    pub unsafe fn to_string_and_dispose(self) -> String {
+        use std::ffi::CStr;
+
+        let c = clang_getCString(self);
+        let string = if c.is_null() {
+            String::new()
+        } else {
+            CStr::from_ptr(c).to_string_lossy().into_owned()
+        };
+        clang_disposeString(self);
+        string
+    }
+}
+
+/// Converts an owned `CXString` into a Rust `String`, disposing of the `CXString` in the
+/// process.
+unsafe fn cxstring_into_string(raw: CXString) -> String {
+    raw.to_string_and_dispose()
+}
+
 #[cfg(feature="gte_clang_3_8")]
 #[derive(Copy, Clone, Debug)]
 #[repr(C)]
@@ -1656,18 +2463,65 @@ pub struct CXStringSet {
 
 default!(#[cfg(feature="gte_clang_3_8")] CXStringSet);
 
-#[derive(Copy, Clone, Debug)]
-#[repr(C)]
-pub struct CXTUResourceUsage {
-    pub data: *mut c_void,
-    pub numEntries: c_uint,
-    pub entries: *mut CXTUResourceUsageEntry,
+/// An owning iterator over the strings in a `*mut CXStringSet`, yielding each as an owned
+/// `String` and calling `clang_disposeStringSet` when dropped, whether or not it was exhausted.
+#[cfg(feature="gte_clang_3_8")]
+pub struct CXStringSetIter(*mut CXStringSet, c_uint);
+
+#[cfg(feature="gte_clang_3_8")]
+impl CXStringSetIter {
+    /// Takes ownership of the given raw `*mut CXStringSet`, returning an iterator over its
+    /// strings.
+    pub unsafe fn new(set: *mut CXStringSet) -> CXStringSetIter {
+        CXStringSetIter(set, 0)
+    }
 }
 
-default!(CXTUResourceUsage);
+#[cfg(feature="gte_clang_3_8")]
+impl Iterator for CXStringSetIter {
+    type Item = String;
 
-#[derive(Copy, Clone, Debug)]
-#[repr(C)]
+    fn next(&mut self) -> Option<String> {
+        use std::ffi::CStr;
+
+        unsafe {
+            let set = &*self.0;
+            if self.1 >= set.Count {
+                return None;
+            }
+
+            let raw = *set.Strings.offset(self.1 as isize);
+            self.1 += 1;
+
+            let c = clang_getCString(raw);
+            Some(if c.is_null() {
+                String::new()
+            } else {
+                CStr::from_ptr(c).to_string_lossy().into_owned()
+            })
+        }
+    }
+}
+
+#[cfg(feature="gte_clang_3_8")]
+impl Drop for CXStringSetIter {
+    fn drop(&mut self) {
+        unsafe { clang_disposeStringSet(self.0) };
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+#[repr(C)]
+pub struct CXTUResourceUsage {
+    pub data: *mut c_void,
+    pub numEntries: c_uint,
+    pub entries: *mut CXTUResourceUsageEntry,
+}
+
+default!(CXTUResourceUsage);
+
+#[derive(Copy, Clone, Debug)]
+#[repr(C)]
 pub struct CXTUResourceUsageEntry {
     pub kind: CXTUResourceUsageKind,
     pub amount: c_ulong,
@@ -1737,6 +2591,8 @@ link! {
     pub fn clang_CXCursorSet_insert(set: CXCursorSet, cursor: CXCursor) -> c_uint;
     pub fn clang_CXIndex_getGlobalOptions(index: CXIndex) -> CXGlobalOptFlags;
     pub fn clang_CXIndex_setGlobalOptions(index: CXIndex, flags: CXGlobalOptFlags);
+    #[cfg(feature="gte_clang_9_0")]
+    pub fn clang_CXIndex_setInvocationEmissionPathOption(index: CXIndex, path: *const c_char);
     #[cfg(feature="gte_clang_3_9")]
     pub fn clang_CXXConstructor_isConvertingConstructor(cursor: CXCursor) -> c_uint;
     #[cfg(feature="gte_clang_3_9")]
@@ -1748,8 +2604,12 @@ link! {
     #[cfg(feature="gte_clang_3_8")]
     pub fn clang_CXXField_isMutable(cursor: CXCursor) -> c_uint;
     pub fn clang_CXXMethod_isConst(cursor: CXCursor) -> c_uint;
+    #[cfg(feature="gte_clang_11_0")]
+    pub fn clang_CXXMethod_isCopyAssignmentOperator(cursor: CXCursor) -> c_uint;
     #[cfg(feature="gte_clang_3_9")]
     pub fn clang_CXXMethod_isDefaulted(cursor: CXCursor) -> c_uint;
+    #[cfg(feature="gte_clang_8_0")]
+    pub fn clang_CXXMethod_isExplicit(cursor: CXCursor) -> c_uint;
     pub fn clang_CXXMethod_isPureVirtual(cursor: CXCursor) -> c_uint;
     pub fn clang_CXXMethod_isStatic(cursor: CXCursor) -> c_uint;
     pub fn clang_CXXMethod_isVirtual(cursor: CXCursor) -> c_uint;
@@ -1801,16 +2661,26 @@ link! {
     #[cfg(feature="gte_clang_3_6")]
     pub fn clang_Cursor_getTemplateArgumentValue(cursor: CXCursor, index: c_uint) -> c_longlong;
     pub fn clang_Cursor_getTranslationUnit(cursor: CXCursor) -> CXTranslationUnit;
+    #[cfg(feature="gte_clang_8_0")]
+    pub fn clang_Cursor_getVarDeclInitializer(cursor: CXCursor) -> CXCursor;
     #[cfg(feature="gte_clang_3_9")]
     pub fn clang_Cursor_hasAttrs(cursor: CXCursor) -> c_uint;
+    #[cfg(feature="gte_clang_8_0")]
+    pub fn clang_Cursor_hasVarDeclExternalStorage(cursor: CXCursor) -> c_int;
+    #[cfg(feature="gte_clang_8_0")]
+    pub fn clang_Cursor_hasVarDeclGlobalStorage(cursor: CXCursor) -> c_int;
     #[cfg(feature="gte_clang_3_7")]
     pub fn clang_Cursor_isAnonymous(cursor: CXCursor) -> c_uint;
+    #[cfg(feature="gte_clang_10_0")]
+    pub fn clang_Cursor_isAnonymousRecordDecl(cursor: CXCursor) -> c_uint;
     pub fn clang_Cursor_isBitField(cursor: CXCursor) -> c_uint;
     pub fn clang_Cursor_isDynamicCall(cursor: CXCursor) -> c_int;
     #[cfg(feature="gte_clang_5_0")]
     pub fn clang_Cursor_isExternalSymbol(cursor: CXCursor, language: *mut CXString, from: *mut CXString, generated: *mut c_uint) -> c_uint;
     #[cfg(feature="gte_clang_3_9")]
     pub fn clang_Cursor_isFunctionInlined(cursor: CXCursor) -> c_uint;
+    #[cfg(feature="gte_clang_7_0")]
+    pub fn clang_Cursor_isInlineNamespace(cursor: CXCursor) -> c_uint;
     #[cfg(feature="gte_clang_3_9")]
     pub fn clang_Cursor_isMacroBuiltin(cursor: CXCursor) -> c_uint;
     #[cfg(feature="gte_clang_3_9")]
@@ -1838,6 +2708,8 @@ link! {
     pub fn clang_EvalResult_isUnsignedInt(result: CXEvalResult) -> c_uint;
     #[cfg(feature="gte_clang_3_6")]
     pub fn clang_File_isEqual(left: CXFile, right: CXFile) -> c_int;
+    #[cfg(feature="gte_clang_7_0")]
+    pub fn clang_File_tryGetRealPathName(file: CXFile) -> CXString;
     pub fn clang_IndexAction_create(index: CXIndex) -> CXIndexAction;
     pub fn clang_IndexAction_dispose(index: CXIndexAction);
     pub fn clang_Location_isFromMainFile(location: CXSourceLocation) -> c_int;
@@ -1849,6 +2721,12 @@ link! {
     pub fn clang_Module_getParent(module: CXModule) -> CXModule;
     pub fn clang_Module_getTopLevelHeader(tu: CXTranslationUnit, module: CXModule, index: c_uint) -> CXFile;
     pub fn clang_Module_isSystem(module: CXModule) -> c_int;
+    #[cfg(feature="gte_clang_6_0")]
+    pub fn clang_PrintingPolicy_dispose(policy: CXPrintingPolicy);
+    #[cfg(feature="gte_clang_6_0")]
+    pub fn clang_PrintingPolicy_getProperty(policy: CXPrintingPolicy, property: CXPrintingPolicyProperty) -> c_uint;
+    #[cfg(feature="gte_clang_6_0")]
+    pub fn clang_PrintingPolicy_setProperty(policy: CXPrintingPolicy, property: CXPrintingPolicyProperty, value: c_uint);
     pub fn clang_Range_isNull(range: CXSourceRange) -> c_int;
     #[cfg(feature="gte_clang_5_0")]
     pub fn clang_TargetInfo_dispose(info: CXTargetInfo);
@@ -1867,6 +2745,8 @@ link! {
     pub fn clang_Type_getOffsetOf(type_: CXType, field: *const c_char) -> c_longlong;
     pub fn clang_Type_getSizeOf(type_: CXType) -> c_longlong;
     pub fn clang_Type_getTemplateArgumentAsType(type_: CXType, index: c_uint) -> CXType;
+    #[cfg(feature="gte_clang_8_0")]
+    pub fn clang_Type_getValueType(type_: CXType) -> CXType;
     #[cfg(feature="gte_clang_5_0")]
     pub fn clang_Type_isTransparentTagTypedef(type_: CXType) -> c_uint;
     #[cfg(feature="gte_clang_3_7")]
@@ -1958,6 +2838,10 @@ link! {
     pub fn clang_getCursorLinkage(cursor: CXCursor) -> CXLinkageKind;
     pub fn clang_getCursorLocation(cursor: CXCursor) -> CXSourceLocation;
     pub fn clang_getCursorPlatformAvailability(cursor: CXCursor, deprecated: *mut c_int, deprecated_message: *mut CXString, unavailable: *mut c_int, unavailable_message: *mut CXString, availability: *mut CXPlatformAvailability, n_availability: c_int) -> c_int;
+    #[cfg(feature="gte_clang_6_0")]
+    pub fn clang_getCursorPrettyPrinted(cursor: CXCursor, policy: CXPrintingPolicy) -> CXString;
+    #[cfg(feature="gte_clang_6_0")]
+    pub fn clang_getCursorPrintingPolicy(cursor: CXCursor) -> CXPrintingPolicy;
     pub fn clang_getCursorReferenceNameRange(cursor: CXCursor, flags: CXNameRefFlags, index: c_uint) -> CXSourceRange;
     pub fn clang_getCursorReferenced(cursor: CXCursor) -> CXCursor;
     pub fn clang_getCursorResultType(cursor: CXCursor) -> CXType;
@@ -2004,6 +2888,8 @@ link! {
     pub fn clang_getLocation(tu: CXTranslationUnit, file: CXFile, line: c_uint, column: c_uint) -> CXSourceLocation;
     pub fn clang_getLocationForOffset(tu: CXTranslationUnit, file: CXFile, offset: c_uint) -> CXSourceLocation;
     pub fn clang_getModuleForFile(tu: CXTranslationUnit, file: CXFile) -> CXModule;
+    #[cfg(feature="gte_clang_8_0")]
+    pub fn clang_getNonReferenceType(type_: CXType) -> CXType;
     pub fn clang_getNullCursor() -> CXCursor;
     pub fn clang_getNullLocation() -> CXSourceLocation;
     pub fn clang_getNullRange() -> CXSourceRange;
@@ -2023,6 +2909,7 @@ link! {
     pub fn clang_getRemappings(file: *const c_char) -> CXRemapping;
     pub fn clang_getRemappingsFromFileList(files: *mut *const c_char, n_files: c_uint) -> CXRemapping;
     pub fn clang_getResultType(type_: CXType) -> CXType;
+    #[cfg(feature="gte_clang_3_6")]
     pub fn clang_getSkippedRanges(tu: CXTranslationUnit, file: CXFile) -> *mut CXSourceRangeList;
     pub fn clang_getSpecializedCursorTemplate(cursor: CXCursor) -> CXCursor;
     pub fn clang_getSpellingLocation(location: CXSourceLocation, file: *mut CXFile, line: *mut c_uint, column: *mut c_uint, offset: *mut c_uint);
@@ -2042,6 +2929,8 @@ link! {
     pub fn clang_getTypedefDeclUnderlyingType(cursor: CXCursor) -> CXType;
     #[cfg(feature="gte_clang_5_0")]
     pub fn clang_getTypedefName(type_: CXType) -> CXString;
+    #[cfg(feature="gte_clang_8_0")]
+    pub fn clang_getUnqualifiedType(type_: CXType) -> CXType;
     pub fn clang_hashCursor(cursor: CXCursor) -> c_uint;
     pub fn clang_indexLoc_getCXSourceLocation(location: CXIdxLoc) -> CXSourceLocation;
     pub fn clang_indexLoc_getFileLocation(location: CXIdxLoc, index_file: *mut CXIdxClientFile, file: *mut CXFile, line: *mut c_uint, column: *mut c_uint, offset: *mut c_uint);
@@ -2132,15 +3021,489 @@ link! {
     pub fn clang_VerbatimLineComment_getText(comment: CXComment) -> CXString;
 }
 
+//================================================
+// Versions
+//================================================
+
+/// A version of `libclang`.
+///
+/// Variants are ordered from oldest to newest so two `Version`s can be compared with `<`/`>=`
+/// (e.g., to gate behavior on "3.9 or later").
+///
+/// There is no `V3_5` variant: every marker symbol this crate knows of was introduced no earlier
+/// than 3.6, so a `libclang` 3.5 (or 3.4 or earlier) is indistinguishable from `Vunknown` and the
+/// two are deliberately conflated rather than given an unreachable variant of its own.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Version {
+    /// `libclang` 3.5 or earlier, or a library whose version could not be determined.
+    Vunknown,
+    V3_6,
+    V3_7,
+    V3_8,
+    V3_9,
+    V4_0,
+    V5_0,
+    V6_0,
+    V7_0,
+    V8_0,
+    V9_0,
+    V10_0,
+    V11_0,
+    /// A `libclang` newer than any version this crate has a marker symbol for.
+    Vnewer,
+}
+
+/// Marker symbols used to probe the version of a loaded `libclang`, newest first.
+///
+/// Each marker is a symbol that was introduced, purely additively, starting with the paired
+/// version. Because `libclang` never removes an exported symbol, the newest marker that is
+/// present in a library is a reliable lower bound on that library's version.
+const VERSION_MARKERS: &'static [(Version, &'static str)] = &[
+    (Version::Vnewer, "clang_CXXMethod_isMoveAssignmentOperator"),
+    (Version::V11_0, "clang_CXXMethod_isCopyAssignmentOperator"),
+    (Version::V10_0, "clang_Cursor_isAnonymousRecordDecl"),
+    (Version::V9_0, "clang_CXIndex_setInvocationEmissionPathOption"),
+    (Version::V8_0, "clang_CXXMethod_isExplicit"),
+    (Version::V7_0, "clang_File_tryGetRealPathName"),
+    (Version::V6_0, "clang_getCursorPrintingPolicy"),
+    (Version::V5_0, "clang_getAddressSpace"),
+    (Version::V4_0, "clang_EvalResult_getAsLongLong"),
+    (Version::V3_9, "clang_Cursor_isFunctionInlined"),
+    (Version::V3_8, "clang_CXXField_isMutable"),
+    (Version::V3_7, "clang_getOffsetOfBase"),
+    (Version::V3_6, "clang_File_isEqual"),
+];
+
+#[cfg(feature="runtime")]
+impl SharedLibrary {
+    /// Returns the path to the `libclang` shared library file this instance was loaded from.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the version of this `libclang` library.
+    ///
+    /// This probes the library for the marker symbols in `VERSION_MARKERS`, newest first, and
+    /// returns the version paired with the first one found, or `Version::Vunknown` if none of
+    /// them is present; it never returns `None`. Callers can use this to gate
+    /// behavior on 3.9-vs-4.0-style API differences without needing to know in advance which
+    /// `libclang` a user has installed.
+    pub fn version(&self) -> Option<Version> {
+        for &(version, marker) in VERSION_MARKERS {
+            let found = unsafe { self.library.get::<unsafe extern fn()>(marker.as_bytes()) }.is_ok();
+            if found {
+                return Some(version);
+            }
+        }
+        Some(Version::Vunknown)
+    }
+}
+
+//================================================
+// Wrapper
+//================================================
+
+/// A safe, idiomatic wrapper layer over the raw FFI types in this crate.
+///
+/// `Cursor` and `Type` are thin newtypes around `CXCursor` and `CXType` that expose the
+/// underlying `clang_*` functions as methods, taking care of `CXString` disposal and the
+/// `CXChildVisitResult`/`CXClientData` dance required by `clang_visitChildren` so callers never
+/// have to touch raw FFI themselves. This mirrors the `clang.rs` wrapper module used by older
+/// versions of `rust-bindgen`.
+///
+/// A `libclang` must already be loaded (see `load`/`load_manually`) before any of these methods
+/// are called; they will panic otherwise, exactly as the raw `clang_*` functions they wrap do.
+#[cfg(feature="clang")]
+pub mod clang {
+    use super::*;
+
+    /// A safe wrapper around a `CXCursor`.
+    #[derive(Copy, Clone)]
+    pub struct Cursor(CXCursor);
+
+    impl Cursor {
+        /// Constructs a new `Cursor` that wraps the given raw `CXCursor`.
+        pub fn new(raw: CXCursor) -> Cursor {
+            Cursor(raw)
+        }
+
+        /// Returns the raw `CXCursor` wrapped by this `Cursor`.
+        pub fn raw(&self) -> CXCursor {
+            self.0
+        }
+
+        /// Returns the kind of this cursor.
+        pub fn kind(&self) -> CXCursorKind {
+            unsafe { clang_getCursorKind(self.0) }
+        }
+
+        /// Returns the spelling of this cursor (e.g., the name of the declaration it refers to).
+        pub fn spelling(&self) -> String {
+            unsafe { cxstring_into_string(clang_getCursorSpelling(self.0)) }
+        }
+
+        /// Returns the source location of this cursor.
+        pub fn location(&self) -> CXSourceLocation {
+            unsafe { clang_getCursorLocation(self.0) }
+        }
+
+        /// Returns the type of the entity this cursor refers to.
+        pub fn cur_type(&self) -> Type {
+            Type::new(unsafe { clang_getCursorType(self.0) })
+        }
+
+        /// Returns the cursor for the definition of the entity this cursor refers to, if any.
+        pub fn definition(&self) -> Option<Cursor> {
+            let definition = unsafe { clang_getCursorDefinition(self.0) };
+            if unsafe { clang_Cursor_isNull(definition) } != 0 {
+                None
+            } else {
+                Some(Cursor(definition))
+            }
+        }
+
+        /// Returns the bit width of this cursor if it refers to a bit field.
+        pub fn bit_width(&self) -> Option<usize> {
+            if unsafe { clang_Cursor_isBitField(self.0) } == 0 {
+                return None;
+            }
+            match unsafe { clang_getFieldDeclBitWidth(self.0) } {
+                width if width < 0 => None,
+                width => Some(width as usize),
+            }
+        }
+
+        /// Returns the value of this cursor if it refers to an enum constant declaration.
+        pub fn enum_val(&self) -> i64 {
+            unsafe { clang_getEnumConstantDeclValue(self.0) as i64 }
+        }
+
+        /// Visits the children of this cursor, calling `f` with each child and its parent.
+        ///
+        /// `f` returns a `CXChildVisitResult` indicating whether `clang_visitChildren` should
+        /// recurse into the child, continue to its next sibling, or stop visiting entirely.
+        pub fn visit<F>(&self, mut f: F) where F: FnMut(Cursor, Cursor) -> CXChildVisitResult {
+            extern fn visit<F>(
+                cursor: CXCursor, parent: CXCursor, data: CXClientData
+            ) -> CXChildVisitResult where F: FnMut(Cursor, Cursor) -> CXChildVisitResult {
+                unsafe { (*(data as *mut F))(Cursor(cursor), Cursor(parent)) }
+            }
+
+            unsafe { clang_visitChildren(self.0, visit::<F>, &mut f as *mut F as CXClientData) };
+        }
+    }
+
+    /// A safe wrapper around a `CXType`.
+    #[derive(Copy, Clone)]
+    pub struct Type(CXType);
+
+    impl Type {
+        /// Constructs a new `Type` that wraps the given raw `CXType`.
+        pub fn new(raw: CXType) -> Type {
+            Type(raw)
+        }
+
+        /// Returns the raw `CXType` wrapped by this `Type`.
+        pub fn raw(&self) -> CXType {
+            self.0
+        }
+
+        /// Returns the kind of this type.
+        pub fn kind(&self) -> CXTypeKind {
+            self.0.kind
+        }
+
+        /// Returns the size of this type in bytes, if it could be determined.
+        pub fn size(&self) -> Option<usize> {
+            match unsafe { clang_Type_getSizeOf(self.0) } {
+                size if size < 0 => None,
+                size => Some(size as usize),
+            }
+        }
+
+        /// Returns the cursor for the declaration of this type.
+        pub fn declaration(&self) -> Cursor {
+            Cursor::new(unsafe { clang_getTypeDeclaration(self.0) })
+        }
+    }
+
+    /// A safe, owning wrapper around a `CXDiagnostic`, disposed of automatically on drop.
+    pub struct Diagnostic(CXDiagnostic);
+
+    impl Diagnostic {
+        /// Takes ownership of the given raw `CXDiagnostic`.
+        pub fn new(raw: CXDiagnostic) -> Diagnostic {
+            Diagnostic(raw)
+        }
+
+        /// Returns the raw `CXDiagnostic` wrapped by this `Diagnostic`.
+        pub fn raw(&self) -> CXDiagnostic {
+            self.0
+        }
+
+        /// Returns the severity of this diagnostic.
+        pub fn severity(&self) -> CXDiagnosticSeverity {
+            unsafe { clang_getDiagnosticSeverity(self.0) }
+        }
+
+        /// Returns the text of this diagnostic.
+        pub fn spelling(&self) -> String {
+            unsafe { cxstring_into_string(clang_getDiagnosticSpelling(self.0)) }
+        }
+
+        /// Returns the `-W` flag that enables this diagnostic and the flag that disables it
+        /// (e.g., `("-Wtautological-compare", "-Wno-tautological-compare")`), the way clang's own
+        /// `DiagnosticGroups` tables organize warnings.
+        pub fn option(&self) -> (String, String) {
+            let mut disable = CXString::default();
+            unsafe {
+                let enable = cxstring_into_string(clang_getDiagnosticOption(self.0, &mut disable));
+                (enable, cxstring_into_string(disable))
+            }
+        }
+
+        /// Returns the source ranges associated with this diagnostic.
+        pub fn ranges(&self) -> Vec<CXSourceRange> {
+            unsafe {
+                let count = clang_getDiagnosticNumRanges(self.0);
+                (0..count).map(|i| clang_getDiagnosticRange(self.0, i)).collect()
+            }
+        }
+
+        /// Returns the Fix-Its associated with this diagnostic, each paired with the source range
+        /// it applies to.
+        pub fn fix_its(&self) -> Vec<(String, CXSourceRange)> {
+            unsafe {
+                let count = clang_getDiagnosticNumFixIts(self.0);
+                (0..count).map(|i| {
+                    let mut range = CXSourceRange::default();
+                    let text = cxstring_into_string(clang_getDiagnosticFixIt(self.0, i, &mut range));
+                    (text, range)
+                }).collect()
+            }
+        }
+
+        /// Formats this diagnostic as a string, as `clang` itself would print it on the command
+        /// line.
+        pub fn format(&self, options: CXDiagnosticDisplayOptions) -> String {
+            unsafe { cxstring_into_string(clang_formatDiagnostic(self.0, options)) }
+        }
+    }
+
+    impl Drop for Diagnostic {
+        fn drop(&mut self) {
+            unsafe { clang_disposeDiagnostic(self.0) };
+        }
+    }
+}
+
+/// RAII ownership wrappers for the raw handles the `clang_create*`/`clang_dispose*` functions
+/// hand out, for callers who want automatic cleanup without adopting the whole of [`clang`].
+///
+/// Each `Owned*` type is a thin newtype around the matching raw handle that calls the
+/// corresponding `clang_dispose*` function when dropped, and `Deref`s to the raw handle so it can
+/// still be passed directly to any `clang_*` function in this crate. This mirrors the managed
+/// handles the Haskell `LibClang` bindings wrap their raw pointers in.
+#[cfg(feature="clang")]
+pub mod owned {
+    use super::*;
+    use std::ops::Deref;
+
+    /// An owning wrapper around a `CXIndex`, disposed of automatically on drop.
+    pub struct OwnedIndex(CXIndex);
+
+    impl OwnedIndex {
+        /// Takes ownership of the given raw `CXIndex`.
+        ///
+        /// The caller must ensure `raw` is not disposed of or wrapped a second time.
+        pub unsafe fn from_raw(raw: CXIndex) -> OwnedIndex {
+            OwnedIndex(raw)
+        }
+    }
+
+    impl Deref for OwnedIndex {
+        type Target = CXIndex;
+
+        fn deref(&self) -> &CXIndex {
+            &self.0
+        }
+    }
+
+    impl Drop for OwnedIndex {
+        fn drop(&mut self) {
+            unsafe { clang_disposeIndex(self.0) };
+        }
+    }
+
+    /// An owning wrapper around a `CXTranslationUnit`, disposed of automatically on drop.
+    pub struct OwnedTranslationUnit(CXTranslationUnit);
+
+    impl OwnedTranslationUnit {
+        /// Takes ownership of the given raw `CXTranslationUnit`.
+        ///
+        /// The caller must ensure `raw` is not disposed of or wrapped a second time.
+        pub unsafe fn from_raw(raw: CXTranslationUnit) -> OwnedTranslationUnit {
+            OwnedTranslationUnit(raw)
+        }
+    }
+
+    impl Deref for OwnedTranslationUnit {
+        type Target = CXTranslationUnit;
+
+        fn deref(&self) -> &CXTranslationUnit {
+            &self.0
+        }
+    }
+
+    impl Drop for OwnedTranslationUnit {
+        fn drop(&mut self) {
+            unsafe { clang_disposeTranslationUnit(self.0) };
+        }
+    }
+
+    /// An owning wrapper around a `CXDiagnostic`, disposed of automatically on drop.
+    ///
+    /// Unlike [`clang::Diagnostic`], this does not expose any accessor methods of its own; it is
+    /// meant for callers who want `Drop`-based cleanup while still calling the raw `clang_*`
+    /// functions directly through `Deref`.
+    pub struct OwnedDiagnostic(CXDiagnostic);
+
+    impl OwnedDiagnostic {
+        /// Takes ownership of the given raw `CXDiagnostic`.
+        ///
+        /// The caller must ensure `raw` is not disposed of or wrapped a second time.
+        pub unsafe fn from_raw(raw: CXDiagnostic) -> OwnedDiagnostic {
+            OwnedDiagnostic(raw)
+        }
+    }
+
+    impl Deref for OwnedDiagnostic {
+        type Target = CXDiagnostic;
+
+        fn deref(&self) -> &CXDiagnostic {
+            &self.0
+        }
+    }
+
+    impl Drop for OwnedDiagnostic {
+        fn drop(&mut self) {
+            unsafe { clang_disposeDiagnostic(self.0) };
+        }
+    }
+
+    /// An owning wrapper around a `CXString`, disposed of automatically on drop.
+    pub struct OwnedString(CXString);
+
+    impl OwnedString {
+        /// Takes ownership of the given raw `CXString`.
+        ///
+        /// The caller must ensure `raw` is not disposed of or wrapped a second time.
+        pub unsafe fn from_raw(raw: CXString) -> OwnedString {
+            OwnedString(raw)
+        }
+    }
+
+    impl Deref for OwnedString {
+        type Target = CXString;
+
+        fn deref(&self) -> &CXString {
+            &self.0
+        }
+    }
+
+    impl Drop for OwnedString {
+        fn drop(&mut self) {
+            unsafe { clang_disposeString(self.0) };
+        }
+    }
+
+    /// An owning wrapper around the `*mut CXCodeCompleteResults` returned by
+    /// `clang_codeCompleteAt`, disposed of automatically on drop.
+    pub struct OwnedCodeCompleteResults(*mut CXCodeCompleteResults);
+
+    impl OwnedCodeCompleteResults {
+        /// Takes ownership of the given raw `*mut CXCodeCompleteResults`.
+        ///
+        /// The caller must ensure `raw` is not disposed of or wrapped a second time.
+        pub unsafe fn from_raw(raw: *mut CXCodeCompleteResults) -> OwnedCodeCompleteResults {
+            OwnedCodeCompleteResults(raw)
+        }
+    }
+
+    impl Deref for OwnedCodeCompleteResults {
+        type Target = *mut CXCodeCompleteResults;
+
+        fn deref(&self) -> &*mut CXCodeCompleteResults {
+            &self.0
+        }
+    }
+
+    impl Drop for OwnedCodeCompleteResults {
+        fn drop(&mut self) {
+            unsafe { clang_disposeCodeCompleteResults(self.0) };
+        }
+    }
+}
+
 mod build {
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::env;
 use std::fs::{self, File};
-use std::io::{Read};
+use std::io::{Read, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
 use std::process::{Command};
 
 use glob::{self, MatchOptions};
 
+thread_local!(static COMMAND_ERRORS: RefCell<HashMap<String, Vec<String>>> = RefCell::new(HashMap::new()));
+
+/// Records that running `command` with `arguments` failed with `error`, so the failure can be
+/// reported later if the overall library search ends up failing too.
+fn record_command_error(command: &str, arguments: &[&str], error: &str) {
+    COMMAND_ERRORS.with(|e| {
+        e.borrow_mut().entry(command.into()).or_insert_with(Vec::new).push(
+            format!("`{} {}`: {}", command, arguments.join(" "), error),
+        );
+    });
+}
+
+/// An RAII guard that flushes any command errors recorded since it was created to `stderr` when
+/// dropped, unless `discard` is called first because the search that triggered them ultimately
+/// succeeded some other way.
+struct CommandErrorGuard(bool);
+
+impl CommandErrorGuard {
+    /// Starts recording command errors, clearing any left over from a previous search.
+    fn new() -> CommandErrorGuard {
+        COMMAND_ERRORS.with(|e| e.borrow_mut().clear());
+        CommandErrorGuard(false)
+    }
+
+    /// Prevents the recorded errors from being flushed to `stderr` on drop.
+    fn discard(mut self) {
+        self.0 = true;
+    }
+
+    /// Returns a summary of the recorded command errors, or an empty string if there are none.
+    fn summary() -> String {
+        COMMAND_ERRORS.with(|e| {
+            e.borrow().values().flat_map(|v| v.iter()).cloned().collect::<Vec<_>>().join("; ")
+        })
+    }
+}
+
+impl Drop for CommandErrorGuard {
+    fn drop(&mut self) {
+        if !self.0 {
+            let summary = CommandErrorGuard::summary();
+            if !summary.is_empty() {
+                eprintln!("warning: some commands failed while searching for a `libclang` library: {}", summary);
+            }
+        }
+    }
+}
+
 /// Returns the components of the version appended to the supplied file.
 fn parse_version(file: &Path) -> Vec<u32> {
     let string = file.to_str().unwrap_or("");
@@ -2173,9 +3536,13 @@ fn contains(directory: &Path, files: &[String]) -> Option<PathBuf> {
 
 /// Runs a console command, returning the output if the command was successfully executed.
 fn run(command: &str, arguments: &[&str]) -> Option<String> {
-    Command::new(command).args(arguments).output().map(|o| {
-        String::from_utf8_lossy(&o.stdout).into_owned()
-    }).ok()
+    match Command::new(command).args(arguments).output() {
+        Ok(o) => Some(String::from_utf8_lossy(&o.stdout).into_owned()),
+        Err(e) => {
+            record_command_error(command, arguments, &e.to_string());
+            None
+        },
+    }
 }
 
 /// Runs `llvm-config`, returning the output if the command was successfully executed.
@@ -2226,10 +3593,67 @@ enum Library {
     Static,
 }
 
+/// Returns the OS this library search should validate candidates against.
+///
+/// This reads `CARGO_CFG_TARGET_OS`, which Cargo sets to the *target* triple's OS when running a
+/// build script — not the host's, which is what `cfg!(target_os = "...")` would report. Falls back
+/// to the host's own `cfg!` value when the variable isn't set, i.e. when this code is running
+/// inside an already-compiled binary (such as the `runtime` feature's library search) rather than
+/// inside a build script.
+fn target_os() -> String {
+    env::var("CARGO_CFG_TARGET_OS").unwrap_or_else(|_| {
+        if cfg!(target_os="linux") { "linux" }
+        else if cfg!(target_os="freebsd") { "freebsd" }
+        else if cfg!(target_os="openbsd") { "openbsd" }
+        else if cfg!(target_os="macos") { "macos" }
+        else if cfg!(target_os="windows") { "windows" }
+        else { "" }.into()
+    })
+}
+
+/// Returns the CPU architecture this library search should validate candidates against, the same
+/// way `target_os` does (`CARGO_CFG_TARGET_ARCH`, falling back to the host's `cfg!` value).
+fn target_arch() -> String {
+    env::var("CARGO_CFG_TARGET_ARCH").unwrap_or_else(|_| {
+        if cfg!(target_arch="x86_64") { "x86_64" }
+        else if cfg!(target_arch="aarch64") { "aarch64" }
+        else if cfg!(target_arch="x86") { "x86" }
+        else { "" }.into()
+    })
+}
+
+/// Returns the pointer width this library search should validate candidates against, the same way
+/// `target_os` does (`CARGO_CFG_TARGET_POINTER_WIDTH`, falling back to the host's `cfg!` value).
+fn target_pointer_width() -> String {
+    env::var("CARGO_CFG_TARGET_POINTER_WIDTH").unwrap_or_else(|_| {
+        if cfg!(target_pointer_width="64") { "64" } else { "32" }.into()
+    })
+}
+
+/// The Mach-O `cputype` for the target architecture, if the target is Mach-O-based.
+fn macho_cpu_type() -> i32 {
+    match &*target_arch() {
+        "x86_64" => 0x01000007, // CPU_TYPE_X86_64
+        "aarch64" => 0x0100000c, // CPU_TYPE_ARM64
+        _ => 0x00000007, // CPU_TYPE_X86
+    }
+}
+
+/// The PE/COFF `Machine` field for the target architecture, if the target is PE/COFF-based.
+fn pe_machine_type() -> u16 {
+    match &*target_arch() {
+        "x86_64" => 0x8664, // IMAGE_FILE_MACHINE_AMD64
+        _ => 0x014c, // IMAGE_FILE_MACHINE_I386
+    }
+}
+
 impl Library {
-    /// Checks whether the supplied file is a valid library for the architecture.
+    /// Checks whether the supplied file is a valid library for the target platform, so a
+    /// cross-compile (e.g. host `x86_64` building for `aarch64`) doesn't silently pick up a
+    /// `libclang` built for the host instead of the target.
     fn check(&self, file: &PathBuf) -> Result<(), String> {
-        if cfg!(any(target_os="freebsd", target_os="linux")) {
+        let target_os = target_os();
+        if target_os == "freebsd" || target_os == "linux" || target_os == "openbsd" {
             if *self == Library::Static {
                 return Ok(());
             }
@@ -2239,13 +3663,80 @@ impl Library {
             if elf[..4] != [127, 69, 76, 70] {
                 return Err("invalid ELF header".into());
             }
-            if cfg!(target_pointer_width="32") && elf[4] != 1 {
+            if target_pointer_width() == "32" && elf[4] != 1 {
                 return Err("invalid ELF class (64-bit)".into());
             }
-            if cfg!(target_pointer_width="64") && elf[4] != 2 {
+            if target_pointer_width() == "64" && elf[4] != 2 {
                 return Err("invalid ELF class (32-bit)".into());
             }
             Ok(())
+        } else if target_os == "macos" {
+            let mut file = try!(File::open(file).map_err(|e| e.to_string()));
+            let mut magic = [0; 4];
+            try!(file.read_exact(&mut magic).map_err(|e| e.to_string()));
+
+            if magic == [0xca, 0xfe, 0xba, 0xbe] || magic == [0xbe, 0xba, 0xfe, 0xca] {
+                // A fat binary, holding one Mach-O image per architecture. Walk the `fat_arch`
+                // table (big-endian, regardless of host byte order) looking for one whose
+                // `cputype` matches the target.
+                let mut count = [0; 4];
+                try!(file.read_exact(&mut count).map_err(|e| e.to_string()));
+                let count = u32::from_be_bytes(count);
+
+                for _ in 0..count {
+                    let mut arch = [0; 8];
+                    try!(file.read_exact(&mut arch).map_err(|e| e.to_string()));
+                    let cpu_type = i32::from_be_bytes([arch[0], arch[1], arch[2], arch[3]]);
+                    if cpu_type == macho_cpu_type() {
+                        return Ok(());
+                    }
+                    // Skip `offset`, `size`, `align` to reach the next entry (`cputype` and
+                    // `cpusubtype` were already consumed into `arch` above).
+                    try!(file.seek(SeekFrom::Current(12)).map_err(|e| e.to_string()));
+                }
+
+                Err("no slice in the fat Mach-O binary matches the target architecture".into())
+            } else if magic == [0xfe, 0xed, 0xfa, 0xce] || magic == [0xce, 0xfa, 0xed, 0xfe] ||
+                      magic == [0xfe, 0xed, 0xfa, 0xcf] || magic == [0xcf, 0xfa, 0xed, 0xfe] {
+                let little_endian = magic[0] == 0xce || magic[0] == 0xcf;
+                let mut cpu_type = [0; 4];
+                try!(file.read_exact(&mut cpu_type).map_err(|e| e.to_string()));
+                let cpu_type = if little_endian {
+                    i32::from_le_bytes(cpu_type)
+                } else {
+                    i32::from_be_bytes(cpu_type)
+                };
+                if cpu_type != macho_cpu_type() {
+                    return Err("Mach-O binary does not match the target architecture".into());
+                }
+                Ok(())
+            } else {
+                Err("invalid Mach-O header".into())
+            }
+        } else if target_os == "windows" {
+            let mut file = try!(File::open(file).map_err(|e| e.to_string()));
+            let mut dos_header = [0; 0x40];
+            try!(file.read_exact(&mut dos_header).map_err(|e| e.to_string()));
+            if dos_header[..2] != [b'M', b'Z'] {
+                return Err("invalid PE/COFF header (missing `MZ` signature)".into());
+            }
+
+            let pe_offset = u32::from_le_bytes([
+                dos_header[0x3c], dos_header[0x3d], dos_header[0x3e], dos_header[0x3f],
+            ]);
+            try!(file.seek(SeekFrom::Start(pe_offset as u64)).map_err(|e| e.to_string()));
+
+            let mut pe_header = [0; 6];
+            try!(file.read_exact(&mut pe_header).map_err(|e| e.to_string()));
+            if pe_header[..4] != [b'P', b'E', 0, 0] {
+                return Err("invalid PE/COFF header (missing `PE\\0\\0` signature)".into());
+            }
+
+            let machine = u16::from_le_bytes([pe_header[4], pe_header[5]]);
+            if machine != pe_machine_type() {
+                return Err("PE/COFF binary does not match the target architecture".into());
+            }
+            Ok(())
         } else {
             Ok(())
         }
@@ -2254,13 +3745,72 @@ impl Library {
 
 /// Searches for a library, returning the directory it can be found in if the search was successful.
 fn find(library: Library, files: &[String], env: &str) -> Result<PathBuf, String> {
+    let errors = CommandErrorGuard::new();
+    let result = find_impl(library, files, env);
+    if result.is_ok() {
+        errors.discard();
+    }
+    result
+}
+
+/// Does the actual work of `find`, reporting failed command executions through the
+/// `CommandErrorGuard` the caller set up.
+fn find_impl(library: Library, files: &[String], env: &str) -> Result<PathBuf, String> {
+    let (candidates, skipped) = search_candidates(library, files, env);
+
+    // Every matching file across every searched directory is considered instead of stopping at
+    // the first hit, so a stale `libclang.so.3.9` found in an earlier directory can't shadow a
+    // newer `libclang.so.16` found later. Ties are broken by preferring a real file over a
+    // symlink, then an explicit `LIBCLANG_PATH`-style override over a backup search.
+    let best = candidates.into_iter().max_by(|a, b| {
+        (&a.1, !a.2, a.3).partial_cmp(&(&b.1, !b.2, b.3)).unwrap()
+    });
+
+    if let Some((path, _, _, _)) = best {
+        return Ok(path);
+    }
+
+    let command_errors = CommandErrorGuard::summary();
+    let message = format!(
+        "couldn't find any of [{}], set the {} environment variable to a path where one of these \
+         files can be found (skipped: [{}]){}",
+        files.iter().map(|f| format!("'{}'", f)).collect::<Vec<_>>().join(", "),
+        env,
+        skipped.join(", "),
+        if command_errors.is_empty() {
+            String::new()
+        } else {
+            format!(" (commands failed: [{}])", command_errors)
+        },
+    );
+    Err(message)
+}
+
+/// Searches for every valid file matching `files`, by the same directory/glob search `find_impl`
+/// performs, instead of narrowing down to a single best match.
+///
+/// Returns each candidate's path, parsed version, whether it's a symlink, and whether it came
+/// from an explicit `LIBCLANG_PATH`-style override rather than a backup search, alongside the list
+/// of files that were found but rejected by `library.check`.
+fn search_candidates(
+    library: Library,
+    files: &[String],
+    env: &str,
+) -> (Vec<(PathBuf, Vec<u32>, bool, bool)>, Vec<String>) {
     let mut skipped = vec![];
+    let mut candidates = vec![];
 
-    /// Attempts to return the supplied file.
+    /// Validates the supplied file and, if valid, adds it to the candidate list.
     macro_rules! try_file {
-        ($file:expr) => ({
+        ($file:expr, $explicit:expr) => ({
             match library.check(&$file) {
-                Ok(_) => return Ok($file),
+                Ok(_) => {
+                    let version = parse_version(&$file);
+                    let is_symlink = fs::symlink_metadata(&$file).map(|m| {
+                        m.file_type().is_symlink()
+                    }).unwrap_or(false);
+                    candidates.push(($file, version, is_symlink, $explicit));
+                },
                 Err(message) => skipped.push(format!("({}: {})", $file.display(), message)),
             }
         });
@@ -2268,9 +3818,9 @@ fn find(library: Library, files: &[String], env: &str) -> Result<PathBuf, String
 
     /// Searches the supplied directory and, on Windows, any relevant sibling directories.
     macro_rules! search_directory {
-        ($directory:ident) => {
+        ($directory:ident, $explicit:expr) => {
             if let Some(file) = contains(&$directory, files) {
-                try_file!(file);
+                try_file!(file, $explicit);
             }
 
             // On Windows, `libclang.dll` is usually found in the LLVM `bin` directory while
@@ -2280,7 +3830,7 @@ fn find(library: Library, files: &[String], env: &str) -> Result<PathBuf, String
             if cfg!(target_os="windows") && $directory.ends_with("lib") {
                 let sibling = $directory.parent().unwrap().join("bin");
                 if let Some(file) = contains(&sibling, files) {
-                    try_file!(file);
+                    try_file!(file, $explicit);
                 }
             }
         }
@@ -2288,7 +3838,17 @@ fn find(library: Library, files: &[String], env: &str) -> Result<PathBuf, String
 
     // Search the directory provided by the relevant environment variable if it is set.
     if let Ok(directory) = env::var(env).map(|d| Path::new(&d).to_path_buf()) {
-        search_directory!(directory);
+        search_directory!(directory, true);
+    }
+
+    // Search the directory returned directly by `llvm-config --libdir`, which on multi-arch
+    // distributions can differ from `--prefix`'s `lib` subdirectory (e.g., a versioned
+    // `/usr/lib/llvm-10/lib`).
+    if let Ok(output) = run_llvm_config(&["--libdir"]) {
+        let libdir = Path::new(output.lines().next().unwrap_or("")).to_path_buf();
+        if let Some(file) = contains(&libdir, files) {
+            try_file!(file, false);
+        }
     }
 
     // Search the `bin` and `lib` subdirectories in the directory returned by
@@ -2297,11 +3857,11 @@ fn find(library: Library, files: &[String], env: &str) -> Result<PathBuf, String
         let directory = Path::new(output.lines().next().unwrap()).to_path_buf();
         let bin = directory.join("bin");
         if let Some(file) = contains(&bin, files) {
-            try_file!(file);
+            try_file!(file, false);
         }
         let lib = directory.join("lib");
         if let Some(file) = contains(&lib, files) {
-            try_file!(file);
+            try_file!(file, false);
         }
     }
 
@@ -2321,24 +3881,48 @@ fn find(library: Library, files: &[String], env: &str) -> Result<PathBuf, String
         options.require_literal_separator = true;
         if let Ok(paths) = glob::glob_with(pattern, &options) {
             for path in paths.filter_map(Result::ok).filter(|p| p.is_dir()) {
-                search_directory!(path);
+                search_directory!(path, false);
             }
         }
     }
 
-    let message = format!(
-        "couldn't find any of [{}], set the {} environment variable to a path where one of these \
-         files can be found (skipped: [{}])",
-        files.iter().map(|f| format!("'{}'", f)).collect::<Vec<_>>().join(", "),
-        env,
-        skipped.join(", "),
-    );
-    Err(message)
+    (candidates, skipped)
 }
 
-/// Searches for a `libclang` shared library, returning the path to such a shared library if the
-/// search was successful.
-pub fn find_shared_library() -> Result<PathBuf, String> {
+/// The range of `libclang` major versions to search for when looking for versioned library
+/// files (e.g., `libclang-11.so`, `libclang.so.11`).
+///
+/// Overridable via the `LIBCLANG_VERSION_MIN`/`LIBCLANG_VERSION_MAX` environment variables, for
+/// distributions that install an LLVM newer or older than this default range.
+fn version_range() -> (u32, u32) {
+    fn read(var: &str, default: u32) -> u32 {
+        env::var(var).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+    }
+    (read("LIBCLANG_VERSION_MIN", 3), read("LIBCLANG_VERSION_MAX", 18))
+}
+
+/// Returns the versioned `libclang` file name patterns to search for (e.g., `libclang-11.so`,
+/// `libclang.so.11`, `libclang-11.dll`), covering the naming schemes used by distributions that
+/// don't create an unversioned `libclang` symlink.
+fn versioned_files() -> Vec<String> {
+    let (min, max) = version_range();
+    let mut files = vec![];
+    for major in min..=max {
+        if cfg!(any(target_os="freebsd", target_os="linux", target_os="openbsd")) {
+            files.push(format!("libclang-{}.so", major));
+            files.push(format!("libclang-{}.so.*", major));
+            files.push(format!("libclang.so.{}", major));
+            files.push(format!("libclang.so.{}.*", major));
+        }
+        if cfg!(target_os="windows") {
+            files.push(format!("libclang-{}.dll", major));
+        }
+    }
+    files
+}
+
+/// Returns the file name patterns that identify a `libclang` shared library on this platform.
+fn shared_library_files() -> Vec<String> {
     let mut files = vec![format!("{}clang{}", env::consts::DLL_PREFIX, env::consts::DLL_SUFFIX)];
     if cfg!(any(target_os="freebsd", target_os="linux", target_os="openbsd")) {
         // Some BSDs and Linux distributions don't create a `libclang.so` symlink, so we need to
@@ -2350,7 +3934,58 @@ pub fn find_shared_library() -> Result<PathBuf, String> {
         // unofficial builds such as MinGW use `clang.dll`.
         files.push("libclang.dll".into());
     }
-    find(Library::Dynamic, &files, "LIBCLANG_PATH")
+    // Distributions that install LLVM at a versioned path (e.g., Debian's `libclang-10.so`,
+    // which isn't paired with an unversioned symlink) need their sonames searched for
+    // explicitly, across a configurable range of major versions.
+    files.extend(versioned_files());
+    files
+}
+
+/// Searches for a `libclang` shared library, returning the path to such a shared library if the
+/// search was successful.
+pub fn find_shared_library() -> Result<PathBuf, String> {
+    find(Library::Dynamic, &shared_library_files(), "LIBCLANG_PATH")
+}
+
+/// Searches only the supplied directory for a `libclang` shared library, without falling back to
+/// `LIBCLANG_PATH`, `llvm-config`, or the OS-specific backup directories that `find_shared_library`
+/// also consults.
+pub fn find_shared_library_in(directory: &Path) -> Option<PathBuf> {
+    contains(directory, &shared_library_files())
+}
+
+/// Enumerates every valid `libclang` shared library found by the same search `find_shared_library`
+/// performs, pairing each with its filename-derived `(major, minor)` version, instead of resolving
+/// to a single best match.
+///
+/// Each searched directory contributes at most one candidate, the highest-versioned match that
+/// `contains` picks there; other matching files in the same directory are not listed.
+///
+/// Ordered best match first (the same order `find_shared_library` would pick from), so a caller
+/// that must match a specific Clang ABI can enumerate its choices and pick one by version before
+/// loading it.
+pub fn list_shared_libraries() -> Vec<(PathBuf, Option<(u32, u32)>)> {
+    let (mut candidates, _) = search_candidates(Library::Dynamic, &shared_library_files(), "LIBCLANG_PATH");
+    candidates.sort_by(|a, b| (&b.1, !b.2, b.3).partial_cmp(&(&a.1, !a.2, a.3)).unwrap());
+    candidates.into_iter().map(|(path, version, _, _)| {
+        let parsed = version.get(0).map(|&major| (major, version.get(1).copied().unwrap_or(0)));
+        (path, parsed)
+    }).collect()
+}
+
+/// Returns the file name patterns that identify a statically-linkable `libclang` archive on this
+/// platform.
+fn static_library_files() -> Vec<String> {
+    vec!["libclang.a".into(), "libclang_static.a".into()]
+}
+
+/// Searches for a statically-linkable `libclang` archive, returning its path if the search was
+/// successful.
+///
+/// Honors an explicit `LIBCLANG_STATIC_PATH` override before falling back to `llvm-config` and the
+/// same OS-specific backup directories `find_shared_library` consults.
+pub fn find_static_library() -> Result<PathBuf, String> {
+    find(Library::Static, &static_library_files(), "LIBCLANG_STATIC_PATH")
 }
 
 /// Returns the name of an LLVM or Clang library from a path to such a library.
@@ -2404,6 +4039,116 @@ fn get_clang_libraries<P: AsRef<Path>>(directory: P) -> Vec<String> {
         CLANG_LIBRARIES.iter().map(|l| l.to_string()).collect()
     }
 }
+
+/// Links a static `libclang` (and its LLVM/Clang archive dependencies and system libraries) into
+/// the binary being built, for use under the `static` Cargo feature.
+///
+/// Honors a `LIBCLANG_STATIC_PATH` directory to search for the Clang archives, falling back to
+/// `llvm-config --libdir` if it isn't set. `llvm-config --libs`/`--system-libs` supply the LLVM and
+/// system libraries those archives depend on; if `llvm-config --libs` is unavailable (e.g., an old
+/// `llvm-config` that doesn't support component-less invocation), this falls back to discovering
+/// libraries by globbing the Clang archive directory directly. The C++ standard library LLVM's
+/// symbols depend on (`c++` on macOS/FreeBSD, `stdc++` elsewhere) is linked last, unless
+/// `llvm-config --system-libs` already accounted for it. If the directory holds no `libclang*.a`
+/// archives, the fixed `CLANG_LIBRARIES` list is linked instead.
+///
+/// # Failures
+///
+/// * Neither `LIBCLANG_STATIC_PATH` is set nor could `llvm-config` be executed
+pub fn link_static() -> Result<(), String> {
+    let libdir = match env::var("LIBCLANG_STATIC_PATH") {
+        Ok(path) => Path::new(&path).to_path_buf(),
+        Err(_) => {
+            let libdir = try!(run_llvm_config(&["--libdir"]));
+            Path::new(libdir.lines().next().unwrap_or("")).to_path_buf()
+        },
+    };
+
+    let clang_libraries = {
+        let libraries = get_clang_libraries(&libdir);
+        if libraries.is_empty() {
+            CLANG_LIBRARIES.iter().map(|l| l.to_string()).collect()
+        } else {
+            libraries
+        }
+    };
+
+    let llvm_libraries = if run_llvm_config(&["--libs"]).is_ok() {
+        get_llvm_libraries()
+    } else {
+        let pattern = libdir.join("libLLVM*.a").to_string_lossy().into_owned();
+        match glob::glob(&pattern) {
+            Ok(paths) => paths.filter_map(|p| p.ok().and_then(|p| get_library_name(&p))).collect(),
+            Err(_) => vec![],
+        }
+    };
+    let system_libraries = run_llvm_config(&["--system-libs"]).map(|o| {
+        o.split_whitespace().filter_map(|p| {
+            if p.starts_with("-l") { Some(p[2..].to_string()) } else { None }
+        }).collect::<Vec<_>>()
+    }).unwrap_or_else(|_| vec![]);
+
+    println!("cargo:rustc-link-search=native={}", libdir.display());
+    for library in clang_libraries.iter().chain(llvm_libraries.iter()) {
+        println!("cargo:rustc-link-lib=static={}", library);
+    }
+    for library in &system_libraries {
+        println!("cargo:rustc-link-lib=dylib={}", library);
+    }
+
+    let cpp_library = if target_os() == "macos" || target_os() == "freebsd" { "c++" } else { "stdc++" };
+    if !system_libraries.iter().any(|l| l == cpp_library) {
+        println!("cargo:rustc-link-lib=dylib={}", cpp_library);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_version_extracts_dotted_numeric_suffix() {
+        assert_eq!(parse_version(Path::new("libclang.so.3.9")), vec![3, 9]);
+        assert_eq!(parse_version(Path::new("libclang.so.16.0.6")), vec![16, 0, 6]);
+    }
+
+    #[test]
+    fn parse_version_treats_missing_suffix_as_empty() {
+        assert_eq!(parse_version(Path::new("libclang.so")), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn candidate_ordering_prefers_highest_version() {
+        let old = (PathBuf::from("/a/libclang.so.3.9"), vec![3, 9], false, false);
+        let new = (PathBuf::from("/b/libclang.so.16"), vec![16], false, false);
+        let best = vec![old, new.clone()].into_iter().max_by(|a, b| {
+            (&a.1, !a.2, a.3).partial_cmp(&(&b.1, !b.2, b.3)).unwrap()
+        });
+        assert_eq!(best, Some(new));
+    }
+
+    #[test]
+    fn candidate_ordering_breaks_version_ties_by_preferring_a_real_file_over_a_symlink() {
+        let symlink = (PathBuf::from("/a/libclang.so.16"), vec![16], true, false);
+        let real = (PathBuf::from("/b/libclang.so.16"), vec![16], false, false);
+        let best = vec![symlink, real.clone()].into_iter().max_by(|a, b| {
+            (&a.1, !a.2, a.3).partial_cmp(&(&b.1, !b.2, b.3)).unwrap()
+        });
+        assert_eq!(best, Some(real));
+    }
+
+    #[test]
+    fn candidate_ordering_breaks_remaining_ties_by_preferring_an_explicit_override() {
+        let backup = (PathBuf::from("/a/libclang.so.16"), vec![16], false, false);
+        let explicit = (PathBuf::from("/b/libclang.so.16"), vec![16], false, true);
+        let best = vec![backup, explicit.clone()].into_iter().max_by(|a, b| {
+            (&a.1, !a.2, a.3).partial_cmp(&(&b.1, !b.2, b.3)).unwrap()
+        });
+        assert_eq!(best, Some(explicit));
+    }
+}
 }
 
 fn main() {
@@ -2413,3 +4158,69 @@ fn main() {
     unsafe { clang_createIndex(0, 1) };
     println!("Did I survive?");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cenum_flags_empty_and_contains() {
+        assert_eq!(CXGlobalOptFlags::empty(), CXGlobalOpt_None);
+        assert!(CXGlobalOptFlags::all().contains(CXGlobalOpt_ThreadBackgroundPriorityForIndexing));
+        assert!(CXGlobalOptFlags::all().contains(CXGlobalOpt_ThreadBackgroundPriorityForEditing));
+        assert!(!CXGlobalOpt_ThreadBackgroundPriorityForIndexing.contains(CXGlobalOpt_ThreadBackgroundPriorityForEditing));
+    }
+
+    #[test]
+    fn cenum_flags_all_is_union_of_every_variant() {
+        let all = CXGlobalOptFlags::all();
+        assert_eq!(all, CXGlobalOpt_ThreadBackgroundPriorityForAll);
+        assert!(all.contains(CXGlobalOpt_ThreadBackgroundPriorityForIndexing));
+        assert!(all.contains(CXGlobalOpt_ThreadBackgroundPriorityForEditing));
+    }
+
+    #[test]
+    fn cenum_flags_bitor_and_bitand() {
+        let combined = CXGlobalOpt_ThreadBackgroundPriorityForIndexing | CXGlobalOpt_ThreadBackgroundPriorityForEditing;
+        assert_eq!(combined, CXGlobalOpt_ThreadBackgroundPriorityForAll);
+        assert_eq!(combined & CXGlobalOpt_ThreadBackgroundPriorityForIndexing, CXGlobalOpt_ThreadBackgroundPriorityForIndexing);
+
+        let mut flags = CXGlobalOpt_None;
+        flags |= CXGlobalOpt_ThreadBackgroundPriorityForIndexing;
+        flags &= CXGlobalOpt_ThreadBackgroundPriorityForIndexing;
+        assert_eq!(flags, CXGlobalOpt_ThreadBackgroundPriorityForIndexing);
+    }
+
+    #[test]
+    fn cenum_flags_not_inverts_bits() {
+        assert_eq!(!CXGlobalOpt_None, CXGlobalOptFlags(!0));
+    }
+
+    #[test]
+    fn cenum_reflection_name_known_variant() {
+        assert_eq!(CXCallingConv_C.name(), Some("CXCallingConv_C"));
+        assert_eq!(CXCallingConv_C.to_string(), "CXCallingConv_C");
+        assert_eq!(format!("{:?}", CXCallingConv_C), "CXCallingConv_C");
+    }
+
+    #[test]
+    fn cenum_reflection_name_unknown_variant() {
+        let unknown = CXCallingConv(12345);
+        assert_eq!(unknown.name(), None);
+        assert_eq!(unknown.to_string(), "12345");
+        assert_eq!(format!("{:?}", unknown), "CXCallingConv(12345)");
+    }
+
+    #[test]
+    fn cenum_reflection_name_version_gated_variant() {
+        assert_eq!(CXCallingConv_X86RegCall.name(), Some("CXCallingConv_X86RegCall"));
+        assert_eq!(CXCallingConv_X86RegCall.introduced_in(), Some(Version::V4_0));
+        assert_eq!(CXCallingConv_C.introduced_in(), None);
+    }
+
+    #[test]
+    fn cenum_reflection_try_from() {
+        assert_eq!(CXCallingConv::try_from(1), Ok(CXCallingConv_C));
+        assert_eq!(CXCallingConv::try_from(12345), Err(12345));
+    }
+}